@@ -1,10 +1,12 @@
-use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::{Duration, Instant};
-use device_query::{DeviceEvents, DeviceState};
-use enigo::{KeyboardControllable, MouseControllable};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread::{self, JoinHandle};
+use crate::backend::{EventSink, EventSource, HeldKeys, InputEvent, ListenGuard};
+use crate::backend::native::{DeviceSource, EnigoSink};
 use crate::canonicalize::{Action, ActionSense, Script};
-use crate::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalKey};
+use crate::clock::{Clock, default_clock};
+use crate::error::TapeError;
+use crate::mission::Mission;
+use crate::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalButton, CanonicalChord, CanonicalKey};
 
 impl Script {
     /// Get the next action to be performed
@@ -28,51 +30,664 @@ impl Script {
 /// An **actor** is a person who performs a [script](../canonicalize/struct.Script.html)  of [action](../act/struct.Action.html)s recorded by a [recorder](../record/struct.Recorder.html)
 ///
 /// ---
-/// - The actor does not internally determine whether you call `act/act_sync` again on the working actor. You need to judge by yourself to avoid unexpected behavior (use [is_working](#method.is_working)).
+/// - Calling `act/act_sync` again on the working actor will get a [TapeError::AlreadyWorking](../error/enum.TapeError.html#variant.AlreadyWorking).
 pub struct Actor {
     /// The type of the action to be acted
     act_type: ActionSense,
-    /// The key that stops the acting
-    stop_signal: Option<CanonicalKey>,
-    /// Whether the actor is acting cyclically
-    cyclic: Arc<Mutex<bool>>,
-    /// A flag indicating whether the actor is working
-    mission_guard: Arc<Mutex<bool>>,
+    /// The key (or chord) that stops the acting
+    stop_signal: Option<CanonicalChord>,
+    /// The key (or chord) that pauses/resumes the acting
+    pause_signal: Option<CanonicalChord>,
+    /// How many times the actor performs the script
+    repeat: Arc<Mutex<Repeat>>,
+    /// The delay between two loops (in milliseconds)
+    loop_delay: Arc<Mutex<i64>>,
+    /// Where the first loop starts
+    start: Offset,
+    /// The lifecycle of the acting
+    mission: Arc<Mission>,
+    /// The state of the acting (kept between actings for the speed)
+    playback: Arc<Mutex<Playback>>,
     /// The script being acted
     script: Script,
+    /// Where the stop signal is listened from
+    source: Arc<dyn EventSource>,
+    /// Where the actions are dispatched to
+    sink: Arc<Mutex<dyn EventSink>>,
+    /// The clock used to time the actions
+    clock: Arc<dyn Clock>,
 }
 
 impl Default for Actor {
     fn default() -> Self {
         Actor {
             act_type: ActionSense::Both,
-            stop_signal: Some(CanonicalKey::Escape),
-            cyclic: Arc::new(Mutex::new(false)),
-            mission_guard: Arc::new(Mutex::new(false)),
+            stop_signal: Some(CanonicalKey::Escape.into()),
+            pause_signal: None,
+            repeat: Arc::new(Mutex::new(Repeat::Once)),
+            loop_delay: Arc::new(Mutex::new(0)),
+            start: Offset::Time(0),
+            mission: Arc::new(Mission::new()),
+            playback: Arc::new(Mutex::new(Playback::new())),
             script: Script::empty(),
+            source: Arc::new(DeviceSource),
+            sink: Arc::new(Mutex::new(EnigoSink::default())),
+            clock: default_clock(),
         }
     }
 }
 
+/// Register a listener on the source for the stop signal and the pause signal (if there is any)
+fn listen_signals(
+    source: &dyn EventSource,
+    stop_signal: Option<CanonicalChord>,
+    pause_signal: Option<CanonicalChord>,
+    mission: &Arc<Mission>,
+) -> Option<ListenGuard> {
+    if stop_signal.is_none() && pause_signal.is_none() {
+        return None;
+    }
+
+    let tmp1 = Arc::clone(mission);
+    let held = HeldKeys::default();
+    Some(source.listen(Arc::new(move |ev: &InputEvent| {
+        let held = held.track(ev);
+        if let InputEvent::KeyDown(key) = *ev {
+            // if the stop signal is pressed, stop the acting
+            if stop_signal.as_ref().is_some_and(|v| v.is_triggered(key, &held)) {
+                tmp1.finish();
+            }
+            // if the pause signal is pressed, pause/resume the acting
+            else if pause_signal.as_ref().is_some_and(|v| v.is_triggered(key, &held)) {
+                tmp1.toggle_pause();
+            }
+        }
+    })))
+}
+
+/// How many times an [actor](struct.Actor.html) performs the script
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Repeat {
+    /// Perform the script once
+    Once,
+    /// Perform the script the given times
+    Times(usize),
+    /// Perform the script again and again until it is stopped
+    Forever,
+    /// Perform the script again and again until the given time (of the clock, in milliseconds),
+    /// the loop going on at that time is performed to the end
+    Until(i64),
+}
+
+impl Repeat {
+    /// Whether another loop may begin after `done` loops have been performed
+    fn allows(&self, done: usize, now: i64) -> bool {
+        match *self {
+            Repeat::Once => done < 1,
+            Repeat::Times(n) => done < n,
+            Repeat::Forever => true,
+            Repeat::Until(deadline) => now < deadline,
+        }
+    }
+}
+
+/// Where to start (or go on) acting on the timeline of the script
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Offset {
+    /// At the time (in milliseconds) on the timeline, the actions at that time are performed
+    Time(i64),
+    /// At the action of the index (in the actions to be acted, that is, after filtered by the type of the actor)
+    Action(usize),
+}
+
+impl Offset {
+    /// Get the cursor (the index of the next action) and the position on the timeline, given the timeline of the actions
+    fn locate(&self, timeline: &[i64], duration: i64) -> (usize, i64) {
+        match *self {
+            Offset::Time(time) => {
+                let position = time.clamp(0, duration.max(0));
+                (timeline.partition_point(|v| *v < position), position)
+            }
+            Offset::Action(index) => match timeline.get(index) {
+                Some(position) => (index, *position),
+                None => (timeline.len(), duration.max(0)),
+            },
+        }
+    }
+}
+
+/// The progress of an acting, see [PlaybackHandle::progress](struct.PlaybackHandle.html#method.progress)
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Progress {
+    /// The number of actions performed in the current loop (that is, the index of the next action)
+    pub cursor: usize,
+    /// The time elapsed on the timeline of the current loop (in milliseconds)
+    pub elapsed: i64,
+    /// The index of the current loop (0 for the first loop)
+    pub iteration: usize,
+    /// The duration of the script (in milliseconds)
+    pub duration: i64,
+}
+
+/// Where an acting is on the timeline of the script.
+///
+/// The position goes on with the clock (scaled by the speed) from an anchor, unless the playhead is frozen (paused or ended),
+/// or held at the anchor by an explicit wait.
+#[derive(Debug, Clone)]
+struct Playhead {
+    /// The time (of the clock) when the playhead is anchored
+    anchor_time: i64,
+    /// The position (on the timeline) when the playhead is anchored
+    anchor_position: i64,
+    /// How long (on the timeline) the playhead stays at the anchor before going on
+    hold: i64,
+    /// Whether the position stays at the anchor
+    frozen: bool,
+    /// How fast the position goes on, compared to the clock
+    speed: f64,
+}
+
+impl Playhead {
+    /// Create a frozen playhead at the beginning of the timeline
+    fn new() -> Playhead {
+        Playhead { anchor_time: 0, anchor_position: 0, hold: 0, frozen: true, speed: 1.0 }
+    }
+
+    /// Get how far the playhead has gone (including the hold) since anchored
+    fn advanced(&self, now: i64) -> i64 {
+        if self.frozen {
+            0
+        } else {
+            ((now - self.anchor_time) as f64 * self.speed) as i64
+        }
+    }
+
+    /// Get the position on the timeline
+    fn position(&self, now: i64) -> i64 {
+        self.anchor_position + (self.advanced(now) - self.hold).max(0)
+    }
+
+    /// Get the time (of the clock) to wait until the playhead reaches the position
+    fn until(&self, now: i64, position: i64) -> i64 {
+        let hold = (self.hold - self.advanced(now)).max(0);
+        ((position - self.position(now) + hold) as f64 / self.speed).ceil() as i64
+    }
+
+    /// Anchor the playhead where it is, keeping the rest of the hold
+    fn rebase(&mut self, now: i64) {
+        let advanced = self.advanced(now);
+        self.anchor_position = self.position(now);
+        self.hold = (self.hold - advanced).max(0);
+        self.anchor_time = now;
+    }
+
+    /// Change the speed, the playhead goes on from where it is (so that there is no jump)
+    fn set_speed(&mut self, now: i64, speed: f64) {
+        self.rebase(now);
+        self.speed = speed;
+    }
+
+    /// Move the playhead to the position (it goes on from there)
+    fn restart(&mut self, now: i64, position: i64) {
+        self.anchor_time = now;
+        self.anchor_position = position;
+        self.hold = 0;
+        self.frozen = false;
+    }
+
+    /// Hold the playhead where it is for `ms` more milliseconds (on the timeline)
+    fn hold(&mut self, now: i64, ms: i64) {
+        self.rebase(now);
+        self.hold += ms.max(0);
+    }
+
+    /// Stop the playhead where it is
+    fn freeze(&mut self, now: i64) {
+        self.rebase(now);
+        self.frozen = true;
+    }
+
+    /// Let the frozen playhead go on from where it stops
+    fn unfreeze(&mut self, now: i64) {
+        self.anchor_time = now;
+        self.frozen = false;
+    }
+}
+
+/// The state of an acting, updated by the acting and read by its [handle](struct.PlaybackHandle.html)
+#[derive(Debug)]
+struct Playback {
+    cursor: usize,
+    iteration: usize,
+    duration: i64,
+    playhead: Playhead,
+    /// The timeline of the actions being acted
+    timeline: Vec<i64>,
+    /// Whether the acting is moved by [seek](struct.PlaybackHandle.html#method.seek) during the pause, and has not followed yet
+    seeked: bool,
+}
+
+/// Change the speed of the acting (if any) and wake it up to wait for the right time, fail if the speed is not positive
+fn change_speed(playback: &Mutex<Playback>, mission: &Mission, clock: &dyn Clock, speed: f64) -> Result<(), TapeError> {
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err(TapeError::InvalidSpeed(speed));
+    }
+
+    playback.lock().unwrap().playhead.set_speed(clock.now(), speed);
+    mission.nudge();
+    Ok(())
+}
+
+impl Playback {
+    fn new() -> Playback {
+        Playback { cursor: 0, iteration: 0, duration: 0, playhead: Playhead::new(), timeline: vec![], seeked: false }
+    }
+
+    /// Move the cursor and the playhead to the offset
+    fn seek(&mut self, now: i64, offset: Offset) {
+        let (cursor, position) = offset.locate(&self.timeline, self.duration);
+        self.cursor = cursor;
+        self.playhead.restart(now, position);
+    }
+
+    fn progress(&self, now: i64) -> Progress {
+        Progress {
+            cursor: self.cursor,
+            elapsed: self.playhead.position(now).clamp(0, self.duration.max(0)),
+            iteration: self.iteration,
+            duration: self.duration,
+        }
+    }
+}
+
+/// Move the paused acting to the offset (in the current loop), fail if it is not paused.
+///
+/// The acting goes on from there once resumed.
+fn seek_paused(playback: &Mutex<Playback>, mission: &Mission, clock: &dyn Clock, offset: Offset) -> Result<(), TapeError> {
+    if !mission.is_paused() {
+        return Err(TapeError::NotPaused);
+    }
+
+    let mut playback = playback.lock().unwrap();
+    let now = clock.now();
+    playback.seek(now, offset);
+    playback.playhead.freeze(now);
+    playback.seeked = true;
+    Ok(())
+}
+
+/// The keys and buttons held down by an acting, and where the mouse is
+#[derive(Debug, Default)]
+struct Holding {
+    keys: Vec<CanonicalKey>,
+    buttons: Vec<CanonicalButton>,
+    position: Option<(i32, i32)>,
+}
+
+impl Holding {
+    /// Get what is held after the actions are performed
+    fn replay(actions: &[Action]) -> Holding {
+        let mut holding = Holding::default();
+        for action in actions {
+            holding.track(&action.action);
+        }
+        holding
+    }
+
+    /// Keep track of the performed action
+    fn track(&mut self, action: &CanonicalAction) {
+        match *action {
+            CanonicalAction::Keyboard(ActionType::Press, key) => {
+                if !self.keys.contains(&key) { self.keys.push(key); }
+            }
+            CanonicalAction::Keyboard(ActionType::Release, key) => self.keys.retain(|v| *v != key),
+            CanonicalAction::Keyboard(ActionType::Move, _) => {}
+            CanonicalAction::Mouse(ev, button, pos) => {
+                self.position = Some(pos);
+                match ev {
+                    ActionType::Press => if !self.buttons.contains(&button) { self.buttons.push(button); },
+                    ActionType::Release => self.buttons.retain(|v| *v != button),
+                    ActionType::Move => {}
+                }
+            }
+            // nothing is held by the others (a chord is tracked key by key while it is performed)
+            _ => {}
+        }
+    }
+
+    /// Release the held keys and buttons (in reverse order), they are still tracked as held
+    fn release(&self, sink: &mut dyn EventSink) {
+        for button in self.buttons.iter().rev() {
+            sink.mouse_up(*button);
+        }
+        for key in self.keys.iter().rev() {
+            sink.key_up(*key);
+        }
+    }
+
+    /// Press the held keys and buttons again, the buttons are pressed where the mouse was
+    fn press(&self, sink: &mut dyn EventSink) {
+        for key in self.keys.iter() {
+            sink.key_down(*key);
+        }
+        if let (false, Some(pos)) = (self.buttons.is_empty(), self.position) {
+            sink.mouse_move_to(pos);
+        }
+        for button in self.buttons.iter() {
+            sink.mouse_down(*button);
+        }
+    }
+
+    /// Move the mouse where it was (if known) and press the held keys and buttons
+    fn restore(&self, sink: &mut dyn EventSink) {
+        if let (true, Some(pos)) = (self.buttons.is_empty(), self.position) {
+            sink.mouse_move_to(pos);
+        }
+        self.press(sink);
+    }
+}
+
+/// Everything an acting needs, taken from the actor once the acting starts.
+///
+/// Shared by [act](struct.Actor.html#method.act) (in a separate thread) and [act_sync](struct.Actor.html#method.act_sync) (in place).
+struct Performance {
+    repeat: Arc<Mutex<Repeat>>,
+    loop_delay: Arc<Mutex<i64>>,
+    stop_signal: Option<CanonicalChord>,
+    pause_signal: Option<CanonicalChord>,
+    mission: Arc<Mission>,
+    script: Script,
+    source: Arc<dyn EventSource>,
+    sink: Arc<Mutex<dyn EventSink>>,
+    clock: Arc<dyn Clock>,
+    playback: Arc<Mutex<Playback>>,
+    holding: Holding,
+    /// How many times the acting has been moved by seeking, to tell whether the action waited for is still due
+    seeks: usize,
+}
+
+impl Performance {
+    /// Wait until the playhead reaches the position on the timeline, sitting out the pauses in the meantime.
+    ///
+    /// The time to wait is scaled by the speed, and worked out again once the speed changes.
+    ///
+    /// Return whether the acting should go on (false if the mission is asked to stop).
+    fn wait_until(&mut self, position: i64) -> bool {
+        loop {
+            let remaining = self.playback.lock().unwrap().playhead.until(self.clock.now(), position);
+            if self.mission.sleep(self.clock.as_ref(), remaining) {
+                return true;
+            }
+
+            // woken up by a pause, a stop, or a change of the time to wait for
+            if self.mission.is_paused() {
+                if !self.sit_out_pause() {
+                    return false;
+                }
+            } else if !self.mission.should_work() {
+                return false;
+            }
+        }
+    }
+
+    /// Freeze the playhead and release what is held until the mission is resumed,
+    /// then press them again and let the playhead go on.
+    ///
+    /// Return whether the acting should go on (false if the mission is asked to stop during the pause).
+    fn sit_out_pause(&mut self) -> bool {
+        self.playback.lock().unwrap().playhead.freeze(self.clock.now());
+        self.holding.release(&mut *self.sink.lock().unwrap());
+
+        if !self.mission.wait_resume() {
+            // nothing is held any more
+            self.holding = Holding::default();
+            return false;
+        }
+
+        // follow the seeking during the pause (if any), what is held there is restored instead
+        let seeked = std::mem::take(&mut self.playback.lock().unwrap().seeked);
+        if seeked {
+            self.script.cursor = self.playback.lock().unwrap().cursor;
+            self.holding = Holding::replay(&self.script.actions[..self.script.cursor]);
+            self.seeks += 1;
+            self.holding.restore(&mut *self.sink.lock().unwrap());
+        } else {
+            self.holding.press(&mut *self.sink.lock().unwrap());
+        }
+        self.playback.lock().unwrap().playhead.unfreeze(self.clock.now());
+        true
+    }
+
+    /// Release the keys and buttons still held by the acting, they are not tracked any more
+    fn release_all(&mut self) {
+        let holding = std::mem::take(&mut self.holding);
+        // the lock may be poisoned by a panic of the sink
+        holding.release(&mut *self.sink.lock().unwrap_or_else(PoisonError::into_inner));
+    }
+
+    /// Perform the action, a text with a delay is typed character by character on the timeline,
+    /// a chord is pressed key by key, and an explicit wait holds the playhead.
+    ///
+    /// Return whether the acting should go on (false if the mission is asked to stop in the middle of the text).
+    fn perform(&mut self, action: &Action) -> bool {
+        let (text, delay) = match &action.action {
+            CanonicalAction::Text { text, delay: Some(delay) } => (text, *delay),
+            // hold the playhead for the explicit wait, so that the following actions are delayed by it
+            CanonicalAction::Wait(ms) => {
+                self.playback.lock().unwrap().playhead.hold(self.clock.now(), *ms);
+                return true;
+            }
+            // dispatch the chord key by key, a press is tracked before it is dispatched and a release after,
+            // so that the modifiers are released even if the dispatching fails halfway
+            CanonicalAction::Chord(chord) => {
+                for key_action in chord.actions() {
+                    let press = matches!(key_action, CanonicalAction::Keyboard(ActionType::Press, _));
+                    if press { self.holding.track(&key_action); }
+                    self.sink.lock().unwrap().perform(&key_action);
+                    if !press { self.holding.track(&key_action); }
+                }
+                return true;
+            }
+            other => {
+                self.sink.lock().unwrap().perform(other);
+                return true;
+            }
+        };
+
+        let seeks = self.seeks;
+        let mut buf = [0; 4];
+        for (index, ch) in text.chars().enumerate() {
+            if index > 0 && !self.wait_until(action.timeline + index as i64 * delay) {
+                return false;
+            }
+            // the rest of the text is not due any more if the acting is moved in the meantime
+            if self.seeks != seeks {
+                return true;
+            }
+            self.sink.lock().unwrap().type_text(ch.encode_utf8(&mut buf));
+        }
+        true
+    }
+
+    /// Perform the script until it is finished (or the mission is asked to stop), then mark the mission as done.
+    ///
+    /// **on_finish** is called every time the script is performed to the end.
+    fn run(mut self, on_finish: Option<Box<dyn Fn() + Send>>) {
+        // register a listener for the stop/pause signal if there is one
+        let guard_signals = listen_signals(self.source.as_ref(), self.stop_signal.clone(), self.pause_signal.clone(), &self.mission);
+
+        // restore what is held at the start (if not the beginning), and start the playhead from there
+        self.holding.restore(&mut *self.sink.lock().unwrap());
+        {
+            let mut playback = self.playback.lock().unwrap();
+            let position = playback.playhead.position(self.clock.now());
+            playback.playhead.restart(self.clock.now(), position);
+        }
+
+        // do acting until the mission is finished (act nothing if not a single loop is allowed)
+        let allowed = self.repeat.lock().unwrap().allows(0, self.clock.now());
+        while allowed && !self.mission.is_stopping() {
+            // get the next action if there is one, otherwise check whether there is another loop or not
+            let seeks = self.seeks;
+            if let Some(next_action) = self.script.next_action() {
+                // wait for the action on the 'timeline', the waiting is cut short once the mission is asked to stop
+                if !self.wait_until(next_action.timeline) {
+                    break;
+                }
+                // the action is not due any more if the acting is moved in the meantime
+                if self.seeks != seeks {
+                    continue;
+                }
+
+                // track it first, so that it is released even if the dispatching fails halfway
+                self.holding.track(&next_action.action);
+                if !self.perform(&next_action) {
+                    break;
+                }
+                self.playback.lock().unwrap().cursor = self.script.cursor;
+            } else {
+                // 1 - check whether the script is finished even if there is no next action
+                if !self.wait_until(self.script.duration) {
+                    break;
+                }
+                if self.seeks != seeks {
+                    continue;
+                }
+
+                // 2 - now that the script has completely ended, release what is still held and execute the callback (if any)
+                self.release_all();
+                if let Some(cb) = &on_finish {
+                    cb()
+                }
+
+                // 3 - check whether there is another loop (and not asked to stop by the callback)
+                let done = self.playback.lock().unwrap().iteration + 1;
+                if self.repeat.lock().unwrap().allows(done, self.clock.now()) && !self.mission.is_stopping() {
+                    // 3.1 - if does, reset the cursor and the playhead (the delay is put before the beginning of the timeline)
+                    self.script.reset_cursor();
+                    let delay = *self.loop_delay.lock().unwrap();
+
+                    let mut playback = self.playback.lock().unwrap();
+                    playback.cursor = 0;
+                    playback.iteration = done;
+                    playback.playhead.restart(self.clock.now(), -delay.max(0));
+                } else {
+                    // 3.2 - if not, finish the mission
+                    break;
+                }
+            }
+        }
+
+        // what is held is released and the mission is marked as done once the performance is dropped
+        drop(guard_signals);
+    }
+}
+
+/// Release what is still held and mark the mission as done, however the acting ends (stopped, finished or panicked)
+impl Drop for Performance {
+    fn drop(&mut self) {
+        self.release_all();
+        self.playback.lock().unwrap_or_else(PoisonError::into_inner).playhead.freeze(self.clock.now());
+        self.mission.done();
+    }
+}
+
+/// The handle of an acting started by [act](struct.Actor.html#method.act).
+///
+/// Dropping the handle does not stop the acting, it just detaches from it.
+#[derive(Debug)]
+pub struct PlaybackHandle {
+    /// The lifecycle of the acting
+    mission: Arc<Mission>,
+    /// The state of the acting
+    playback: Arc<Mutex<Playback>>,
+    /// The clock used to time the actions
+    clock: Arc<dyn Clock>,
+    /// The acting thread
+    thread: JoinHandle<()>,
+}
+
+impl PlaybackHandle {
+    /// Get the progress of the acting
+    ///
+    /// Once the acting is paused or finished, the progress stays where it stops.
+    pub fn progress(&self) -> Progress {
+        self.playback.lock().unwrap().progress(self.clock.now())
+    }
+
+    /// Check whether the acting is still going on (including the paused one)
+    pub fn is_working(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    /// Check whether the acting is paused
+    pub fn is_paused(&self) -> bool {
+        self.mission.is_paused()
+    }
+
+    /// Pause the acting, see [Actor::pause](struct.Actor.html#method.pause)
+    pub fn pause(&self) {
+        self.mission.pause();
+    }
+
+    /// Resume the paused acting, see [Actor::resume](struct.Actor.html#method.resume)
+    pub fn resume(&self) {
+        self.mission.resume();
+    }
+
+    /// Change the speed of the acting, see [Actor::set_speed](struct.Actor.html#method.set_speed)
+    pub fn set_speed(&self, speed: f64) -> Result<(), TapeError> {
+        change_speed(&self.playback, &self.mission, self.clock.as_ref(), speed)
+    }
+
+    /// Move the paused acting to the offset, see [Actor::seek](struct.Actor.html#method.seek)
+    pub fn seek(&self, offset: Offset) -> Result<(), TapeError> {
+        seek_paused(&self.playback, &self.mission, self.clock.as_ref(), offset)
+    }
+
+    /// Interrupt the acting (it will do nothing if the acting is already finished)
+    pub fn stop(&self) {
+        self.mission.finish();
+    }
+
+    /// Block until the acting is finished (by the end of the script, the stop signal or [stop](#method.stop))
+    pub fn join(self) {
+        // a panic of the callback has nothing more to tell
+        let _ = self.thread.join();
+    }
+}
+
 impl Actor {
     /// Whether the actor has a stop signal
     pub fn has_stop_signal(&self) -> bool {
         self.stop_signal.is_some()
     }
 
+    /// Whether the acting could never end, that is, it is repeated forever without a stop signal
+    pub fn is_endless(&self) -> bool {
+        self.stop_signal.is_none() && *self.repeat.lock().unwrap() == Repeat::Forever
+    }
+
     /// Check whether the actor is working
     pub fn is_working(&self) -> bool {
-        *self.mission_guard.lock().unwrap()
+        self.mission.is_working()
     }
 
     /// Create a new actor
-    pub fn new(script: Script, cyclic: bool, act_type: ActionSense, stop_signal: Option<CanonicalKey>) -> Self {
+    pub fn new(script: Script, cyclic: bool, act_type: ActionSense, stop_signal: Option<CanonicalChord>) -> Self {
         Actor {
             act_type,
             stop_signal,
-            cyclic: Arc::new(Mutex::new(cyclic)),
-            mission_guard: Arc::new(Mutex::new(false)),
+            pause_signal: None,
+            repeat: Arc::new(Mutex::new(if cyclic { Repeat::Forever } else { Repeat::Once })),
+            loop_delay: Arc::new(Mutex::new(0)),
+            start: Offset::Time(0),
+            mission: Arc::new(Mission::new()),
+            playback: Arc::new(Mutex::new(Playback::new())),
             script,
+            source: Arc::new(DeviceSource),
+            sink: Arc::new(Mutex::new(EnigoSink::default())),
+            clock: default_clock(),
         }
     }
 
@@ -83,11 +698,34 @@ impl Actor {
         self.script = script;
     }
 
-    /// Set whether the actor is acting cyclically.
+    /// Set whether the actor is acting cyclically, a shortcut of [set_repeat](#method.set_repeat) with [Repeat::Forever](enum.Repeat.html#variant.Forever) or [Repeat::Once](enum.Repeat.html#variant.Once).
     ///
     /// Can affect the current acting cause 'cyclic' will be checked before every loop.
     pub fn set_cyclic(&mut self, cyclic: bool) {
-        *self.cyclic.lock().unwrap() = cyclic;
+        self.set_repeat(if cyclic { Repeat::Forever } else { Repeat::Once });
+    }
+
+    /// Set how many times the actor performs the script (default to [Repeat::Once](enum.Repeat.html#variant.Once)).
+    ///
+    /// Can affect the current acting cause the policy will be checked before every loop.
+    pub fn set_repeat(&mut self, repeat: Repeat) {
+        *self.repeat.lock().unwrap() = repeat;
+    }
+
+    /// Set the delay between two loops in milliseconds (default to 0).
+    ///
+    /// The delay is on the timeline (before the beginning of the next loop), so it is scaled by the speed and can be paused like every other wait.
+    /// Can affect the current acting cause the delay will be checked before every loop.
+    pub fn set_loop_delay(&mut self, delay: i64) {
+        *self.loop_delay.lock().unwrap() = delay;
+    }
+
+    /// Set where the first loop starts (default to the beginning), the following loops start from the beginning.
+    ///
+    /// The keys and buttons held at that point are pressed and the mouse is moved to where it was, before going on.
+    /// This has no effect on the current acting. (The offset is copied once [act](#method.act) is called)
+    pub fn set_start(&mut self, start: Offset) {
+        self.start = start;
     }
 
     /// Set the type of the action to be acted.
@@ -97,114 +735,160 @@ impl Actor {
         self.act_type = act_type;
     }
 
-    /// Set the key that stops the acting.
+    /// Set the key (or chord, e.g. "ctrl+shift+q") that stops the acting.
     ///
     /// This has no effect on the current acting. (The signal is cloned once [act](#method.act) is called)
-    pub fn set_stop_signal(&mut self, stop_signal: Option<CanonicalKey>) {
+    pub fn set_stop_signal(&mut self, stop_signal: Option<CanonicalChord>) {
         self.stop_signal = stop_signal;
     }
 
+    /// Set the key (or chord) that pauses the acting, and resumes it once pressed again (default to None).
+    ///
+    /// This has no effect on the current acting. (The signal is cloned once [act](#method.act) is called)
+    pub fn set_pause_signal(&mut self, pause_signal: Option<CanonicalChord>) {
+        self.pause_signal = pause_signal;
+    }
+
+    /// Set where the stop signal is listened from, default to the devices ([DeviceSource](../backend/native/struct.DeviceSource.html))
+    ///
+    /// This has no effect on the current acting. (The listener is set once [act](#method.act) is called)
+    pub fn set_source(&mut self, source: Arc<dyn EventSource>) {
+        self.source = source;
+    }
+
+    /// Set where the actions are dispatched to, default to the devices ([EnigoSink](../backend/native/struct.EnigoSink.html))
+    ///
+    /// This has no effect on the current acting. (The sink is cloned once [act](#method.act) is called)
+    pub fn set_sink(&mut self, sink: Arc<Mutex<dyn EventSink>>) {
+        self.sink = sink;
+    }
+
+    /// Get the speed of acting
+    pub fn speed(&self) -> f64 {
+        self.playback.lock().unwrap().playhead.speed
+    }
+
+    /// Set the speed of acting (default to 1.0), e.g. 2.0 for twice as fast and 0.5 for half as fast.
+    ///
+    /// Every wait is scaled, including the trailing duration of the script.
+    /// Can affect the current acting, which goes on from where it is at the new speed.
+    /// ---
+    /// return:
+    /// - **Ok(())**: The speed is set
+    /// - **Err(TapeError::InvalidSpeed)**: The speed is not a positive finite number
+    pub fn set_speed(&mut self, speed: f64) -> Result<(), TapeError> {
+        change_speed(&self.playback, &self.mission, self.clock.as_ref(), speed)
+    }
+
+    /// Set the clock used to time the actions, default to the [SystemClock](../clock/struct.SystemClock.html)
+    ///
+    /// This has no effect on the current acting. (The clock is cloned once [act](#method.act) is called)
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Start the mission and take what the acting needs, fail if the actor is already working
+    fn begin(&mut self) -> Result<Performance, TapeError> {
+        self.mission.begin()?;
+        self.script.reset_cursor();
+
+        let mut script = self.script.to_filtered(self.act_type.clone());
+        {
+            let mut playback = self.playback.lock().unwrap();
+            playback.iteration = 0;
+            playback.duration = script.duration;
+            playback.timeline = script.actions.iter().map(|v| v.timeline).collect();
+            playback.seeked = false;
+            playback.seek(self.clock.now(), self.start);
+            playback.playhead.freeze(self.clock.now());
+            script.cursor = playback.cursor;
+        }
+        let holding = Holding::replay(&script.actions[..script.cursor]);
+
+        Ok(Performance {
+            repeat: Arc::clone(&self.repeat),
+            loop_delay: Arc::clone(&self.loop_delay),
+            stop_signal: self.stop_signal.clone(),
+            pause_signal: self.pause_signal.clone(),
+            mission: Arc::clone(&self.mission),
+            script,
+            source: Arc::clone(&self.source),
+            sink: Arc::clone(&self.sink),
+            clock: Arc::clone(&self.clock),
+            playback: Arc::clone(&self.playback),
+            holding,
+            seeks: 0,
+        })
+    }
+
     /// Start acting.
     /// (asynchronously, you can use [finish](#method.finish) to interrupt the acting)
     ///
     /// This will run in a separate thread, so it will not block the main thread.
-    /// On the other hand, you may need to wait in the main thread for the acting to finish.
+    /// On the other hand, you may need to [join](struct.PlaybackHandle.html#method.join) the returned handle to wait for the acting to finish.
     /// ---
     /// **on_finish**: a callback function that will be called when every acting is finished
     /// - If set to None, do nothing when the acting is finished
     /// - If set to Some(f), call f(script) when every acting is finished
     /// ---
+    /// return:
+    /// - **Ok(PlaybackHandle)**: The acting is started, see [PlaybackHandle](struct.PlaybackHandle.html)
+    /// - **Err(TapeError::AlreadyWorking)**: The actor is already working
+    /// ---
     /// If you want to use synchronous acting, please call [act_sync](#method.act_sync).
-    pub fn act(&mut self, on_finish: Option<Box<dyn Fn() + Send>>) {
-        // set the working flag
-        *self.mission_guard.lock().unwrap() = true;
-        self.script.reset_cursor();
+    pub fn act(&mut self, on_finish: Option<Box<dyn Fn() + Send>>) -> Result<PlaybackHandle, TapeError> {
+        let performance = self.begin()?;
+        let mission = Arc::clone(&performance.mission);
+        let playback = Arc::clone(&performance.playback);
+        let thread = thread::spawn(move || performance.run(on_finish));
+
+        Ok(PlaybackHandle {
+            mission,
+            playback,
+            clock: Arc::clone(&self.clock),
+            thread,
+        })
+    }
 
-        let cyclic_flag = Arc::clone(&self.cyclic);
-        let stop_signal = self.stop_signal.clone();
-        let mission_guard = Arc::clone(&self.mission_guard);
-        let mut script_copy = self.script.to_filtered(self.act_type.clone());
-
-        thread::spawn(move || {
-            let mut eg = enigo::Enigo::new();
-
-            // register a listener for the stop signal if there is one
-            let _guard_quit;
-            if let Some(quit_key) = stop_signal {
-                let tmp1 = Arc::clone(&mission_guard);
-                _guard_quit = DeviceState::new().on_key_down(move |key| {
-                    // if the stop signal is pressed, stop the acting
-                    if key == &quit_key {
-                        *tmp1.lock().unwrap() = false;
-                        return;
-                    }
-                })
-            }
+    /// Interrupt the actor from acting (it will do nothing if the actor is not acting)
+    pub fn finish(&mut self) {
+        self.mission.finish();
+    }
 
-            // use 'Instant' to record the beginning time
-            let mut begin_time = Instant::now();
-
-            // do acting until the mission is finished
-            while *mission_guard.lock().unwrap() {
-                // get the elapsed time, calculate the frame on the 'timeline'
-                let elapsed_ms = begin_time.elapsed().as_millis() as i64;
-
-                // get the next action if there is one, otherwise check whether the script is cyclic or not
-                if let Some(next_action) = script_copy.next_action() {
-                    let wait_time = next_action.timeline - elapsed_ms;
-                    if wait_time > 0 { thread::sleep(Duration::from_millis(wait_time as u64)); }
-
-                    match next_action.action {
-                        CanonicalAction::Keyboard(t, k) => match t {
-                            ActionType::Press => if let Ok(enigo_key) = k.try_into() {
-                                eg.key_down(enigo_key);
-                            },
-                            ActionType::Release => if let Ok(enigo_key) = k.try_into() {
-                                eg.key_up(enigo_key);
-                            },
-                            // ignore ActionType::Move cause it is not a  valid keyboard action type
-                            _ => {}
-                        }
-                        CanonicalAction::Mouse(t, b, p) => match t {
-                            ActionType::Press => if let Ok(enigo_button) = b.try_into() {
-                                eg.mouse_down(enigo_button);
-                            },
-                            ActionType::Release => if let Ok(enigo_button) = b.try_into() {
-                                eg.mouse_up(enigo_button);
-                            },
-                            ActionType::Move => eg.mouse_move_to(p.0, p.1),
-                        }
-                    }
-                } else {
-                    // 1 - check whether the script is finished even if there is no next action
-                    if elapsed_ms < script_copy.duration {
-                        thread::sleep(Duration::from_millis((script_copy.duration - elapsed_ms) as u64));
-                    }
+    /// Check whether the acting is paused
+    pub fn is_paused(&self) -> bool {
+        self.mission.is_paused()
+    }
 
-                    // 2. - now that the mission has completely ended, execute the callback (if any)
-                    if let Some(cb) = &on_finish {
-                        cb()
-                    }
+    /// Get the progress of the current (or the last) acting, whether it is started by [act](#method.act) or [act_sync](#method.act_sync)
+    ///
+    /// Once the acting is paused or finished, the progress stays where it stops.
+    pub fn progress(&self) -> Progress {
+        self.playback.lock().unwrap().progress(self.clock.now())
+    }
 
-                    // 3 - check whether it is cyclic
-                    if *cyclic_flag.lock().unwrap() {
-                        // 3.1 - if does, reset the cursor and the beginning time
-                        script_copy.reset_cursor();
-                        begin_time = Instant::now();
-                    } else {
-                        // 3.2 - if not, finish the mission
-                        *mission_guard.lock().unwrap() = false;
-                        return;
-                    }
-                }
-            }
-        });
+    /// Pause the acting (it will do nothing if the actor is not acting).
+    ///
+    /// The timeline is frozen, so that the remaining delay to the next action is preserved once resumed.
+    /// The keys and buttons held by the acting are released during the pause, and pressed again once resumed.
+    pub fn pause(&self) {
+        self.mission.pause();
     }
 
-    /// Interrupt the actor from acting (it will do nothing if the actor is not acting)
-    pub fn finish(&mut self) {
-        // set the working flag to false
-        *self.mission_guard.lock().unwrap() = false;
+    /// Resume the paused acting (it will do nothing if the acting is not paused)
+    pub fn resume(&self) {
+        self.mission.resume();
+    }
+
+    /// Move the paused acting to the offset in the current loop, it goes on from there once resumed.
+    ///
+    /// The keys and buttons held at that point are pressed and the mouse is moved to where it was (instead of the ones held before the pause).
+    /// ---
+    /// return:
+    /// - **Ok(())**: The acting is moved
+    /// - **Err(TapeError::NotPaused)**: The actor is not acting, or the acting is not paused
+    pub fn seek(&self, offset: Offset) -> Result<(), TapeError> {
+        seek_paused(&self.playback, &self.mission, self.clock.as_ref(), offset)
     }
 
     /// Start acting
@@ -212,90 +896,17 @@ impl Actor {
     /// ---
     /// return:
     /// - **Ok(())**: The script being acted
-    /// - **Err(())**: If you have not set the stop signal and the script is set to be cyclic, this will return a Err(())
+    /// - **Err(TapeError::NoStopSignal)**: If you have not set the stop signal and the script is set to be repeated forever, this will return a Err
     /// This is by design rather than a bug (image a situation where you want to act cyclically but you forget to set the stop signal, then the actor will never stop)
+    /// - **Err(TapeError::AlreadyWorking)**: The actor is already working
     /// ---
     /// If you want to use asynchronous acting, please call [act](#method.act).
-    pub fn act_sync(&mut self) -> Result<(), ()> {
-        if self.stop_signal.is_none() && *self.cyclic.lock().unwrap() {
-            return Err(());
+    pub fn act_sync(&mut self) -> Result<(), TapeError> {
+        if self.is_endless() {
+            return Err(TapeError::NoStopSignal);
         }
 
-        // set the working flag
-        *self.mission_guard.lock().unwrap() = true;
-        self.script.reset_cursor();
-
-        let cyclic_flag = Arc::clone(&self.cyclic);
-        let mission_guard = Arc::clone(&self.mission_guard);
-        let mut script_copy = self.script.to_filtered(self.act_type.clone());
-
-        let mut eg = enigo::Enigo::new();
-
-        // register a listener for the stop signal if there is one
-        let _guard_quit;
-        if let Some(quit_key) = self.stop_signal {
-            let tmp1 = Arc::clone(&mission_guard);
-            _guard_quit = DeviceState::new().on_key_down(move |key| {
-                // if the stop signal is pressed, stop the acting
-                if key == &quit_key {
-                    *tmp1.lock().unwrap() = false;
-                    return;
-                }
-            })
-        }
-
-        // use 'Instant' to record the beginning time
-        let mut begin_time = Instant::now();
-
-        // do acting until the mission is finished
-        while *mission_guard.lock().unwrap() {
-            // get the elapsed time, calculate the frame on the 'timeline'
-            let elapsed_ms = begin_time.elapsed().as_millis() as i64;
-
-            // get the next action if there is one, otherwise check whether the script is cyclic or not
-            if let Some(next_action) = script_copy.next_action() {
-                let wait_time = next_action.timeline - elapsed_ms;
-                if wait_time > 0 { thread::sleep(Duration::from_millis(wait_time as u64)); }
-
-                match next_action.action {
-                    CanonicalAction::Keyboard(t, k) => match t {
-                        ActionType::Press => if let Ok(enigo_key) = k.try_into() {
-                            eg.key_down(enigo_key);
-                        },
-                        ActionType::Release => if let Ok(enigo_key) = k.try_into() {
-                            eg.key_up(enigo_key);
-                        },
-                        // ignore ActionType::Move cause it is not a  valid keyboard action type
-                        _ => {}
-                    }
-                    CanonicalAction::Mouse(t, b, p) => match t {
-                        ActionType::Press => if let Ok(enigo_button) = b.try_into() {
-                            eg.mouse_down(enigo_button);
-                        },
-                        ActionType::Release => if let Ok(enigo_button) = b.try_into() {
-                            eg.mouse_up(enigo_button);
-                        },
-                        ActionType::Move => eg.mouse_move_to(p.0, p.1),
-                    }
-                }
-            } else {
-                // 1 - check whether the script is finished even if there is no next action
-                if elapsed_ms < script_copy.duration {
-                    thread::sleep(Duration::from_millis((script_copy.duration - elapsed_ms) as u64));
-                }
-
-                // 2 - check whether it is cyclic
-                if *cyclic_flag.lock().unwrap() {
-                    // 2.1 - if cyclic, reset the cursor and the beginning time
-                    script_copy.reset_cursor();
-                    begin_time = Instant::now();
-                } else {
-                    // 2.2 - if not, finish the mission
-                    *mission_guard.lock().unwrap() = false;
-                    break;
-                }
-            }
-        }
+        self.begin()?.run(None);
 
         Ok(())
     }
@@ -304,12 +915,826 @@ impl Actor {
 #[cfg(test)]
 mod unit_test {
     use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
+    use std::sync::{Arc, Mutex};
     use device_query::Keycode;
     use enigo::{Key, KeyboardControllable, MouseButton, MouseControllable};
-    use crate::act::Actor;
-    use crate::canonicalize::declaration::{ActionType, CanonicalKey};
+    use crate::act::{Actor, Offset, PlaybackHandle, Progress, Repeat};
+    use crate::backend::{EventSink, InputEvent};
+    use crate::backend::mock::{MockSink, MockSource};
+    use crate::canonicalize::declaration::{ActionType, CanonicalButton, CanonicalKey};
     use crate::canonicalize::{ActionSense, Script};
+    use crate::clock::{Clock, VirtualClock};
+    use crate::error::TapeError;
+
+    // region mock script raw
+    const MOCK_SCRIPT_RAW: &str = r##"
+name = "mock"
+ctime = 1695739924720
+duration = 60
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action]
+Keyboard = ["Press", "KeyA"]
+
+[[actions]]
+ctime = 1695739924740
+timeline = 20
+
+[actions.action]
+Mouse = ["Move", "Unknown", [30, 40]]
+
+[[actions]]
+ctime = 1695739924750
+timeline = 30
+
+[actions.action]
+Mouse = ["Press", "Left", [30, 40]]
+
+[[actions]]
+ctime = 1695739924760
+timeline = 40
+
+[actions.action]
+Mouse = ["Release", "Left", [30, 40]]
+
+[[actions]]
+ctime = 1695739924770
+timeline = 50
+
+[actions.action]
+Keyboard = ["Release", "KeyA"]
+        "##;
+    // endregion
+
+    /// Poll until the condition holds, fail if it does not in time (instead of sleeping for a fixed time)
+    fn wait_for(condition: impl Fn() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !condition() {
+            assert!(Instant::now() < deadline, "the condition does not hold in time");
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    #[test]
+    fn act_sync_mock() {
+        let script = Script::load(MOCK_SCRIPT_RAW).unwrap();
+        let sink = MockSink::new();
+        let mut actor = Actor::new(script, false, ActionSense::Both, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+
+        actor.act_sync().unwrap();
+
+        assert!(!actor.is_working());
+        assert_eq!(sink.dispatched(), vec![
+            InputEvent::KeyDown(CanonicalKey::KeyA),
+            InputEvent::MouseMove((30, 40)),
+            InputEvent::MouseDown(CanonicalButton::Left, (30, 40)),
+            InputEvent::MouseUp(CanonicalButton::Left, (30, 40)),
+            InputEvent::KeyUp(CanonicalKey::KeyA),
+        ]);
+
+        // a cyclic actor without stop signal never stops
+        actor.set_cyclic(true);
+        assert_eq!(actor.act_sync().unwrap_err(), TapeError::NoStopSignal);
+        actor.set_cyclic(false);
+
+        // filtered by the type of the actor
+        sink.clear();
+        actor.set_act_type(ActionSense::Keyboard);
+        actor.act_sync().unwrap();
+        assert_eq!(sink.dispatched(), vec![
+            InputEvent::KeyDown(CanonicalKey::KeyA),
+            InputEvent::KeyUp(CanonicalKey::KeyA),
+        ]);
+    }
+
+    #[test]
+    fn act_timing() {
+        let clock = VirtualClock::new(0);
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(MOCK_SCRIPT_RAW).unwrap(), false, ActionSense::Both, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        actor.act_sync().unwrap();
+
+        // every action is dispatched exactly at its timeline, and the trailing duration is waited
+        let times: Vec<i64> = sink.dispatched_at().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(times, vec![10, 20, 30, 40, 50]);
+        assert_eq!(clock.now(), 60);
+    }
+
+    #[test]
+    fn act_cyclic_timing() {
+        let clock = VirtualClock::new(0);
+        let source = MockSource::new();
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(MOCK_SCRIPT_RAW).unwrap(), true, ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
+        actor.set_source(Arc::new(source.clone()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        // stop after the third loop
+        let loops = Arc::new(Mutex::new(0));
+        let tmp1 = Arc::clone(&loops);
+        actor.act(Some(Box::new(move || {
+            let mut loops = tmp1.lock().unwrap();
+            *loops += 1;
+            if *loops == 3 {
+                source.emit(InputEvent::KeyDown(CanonicalKey::Escape));
+            }
+        }))).unwrap();
+
+        while actor.is_working() {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        // every loop restarts right after the duration of the previous one
+        assert_eq!(*loops.lock().unwrap(), 3);
+        let times: Vec<i64> = sink.dispatched_at().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(times, vec![10, 50, 70, 110, 130, 170]);
+    }
+
+    #[test]
+    fn act_progress() {
+        let clock = VirtualClock::new(0);
+        let source = MockSource::new();
+        let mut actor = Actor::new(Script::load(MOCK_SCRIPT_RAW).unwrap(), false, ActionSense::Both, Some(CanonicalKey::Escape.into()));
+        actor.set_source(Arc::new(source.clone()));
+        actor.set_sink(Arc::new(Mutex::new(MockSink::new())));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        let handle = actor.act(None).unwrap();
+        let progress = |handle: &PlaybackHandle| {
+            while handle.is_working() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            handle.progress()
+        };
+        assert_eq!(progress(&handle), Progress { cursor: 5, elapsed: 60, iteration: 0, duration: 60 });
+        handle.join();
+
+        // stop after the third loop
+        actor.set_cyclic(true);
+        let loops = Arc::new(Mutex::new(0));
+        let tmp1 = Arc::clone(&loops);
+        let handle = actor.act(Some(Box::new(move || {
+            let mut loops = tmp1.lock().unwrap();
+            *loops += 1;
+            if *loops == 3 {
+                source.emit(InputEvent::KeyDown(CanonicalKey::Escape));
+            }
+        }))).unwrap();
+        assert_eq!(progress(&handle), Progress { cursor: 5, elapsed: 60, iteration: 2, duration: 60 });
+
+        // the progress stays where it stops
+        clock.advance(1000);
+        assert_eq!(handle.progress().elapsed, 60);
+
+        // the actor tells the progress of the last acting as well, however it is started
+        assert_eq!(actor.progress(), handle.progress());
+        actor.set_cyclic(false);
+        actor.act_sync().unwrap();
+        assert_eq!(actor.progress(), Progress { cursor: 5, elapsed: 60, iteration: 0, duration: 60 });
+    }
+
+    #[test]
+    fn act_progress_live() {
+        // a script with a long gap between the actions
+        let clock = VirtualClock::new(0);
+        let mut script = Script::with_clock(Arc::new(clock.clone()));
+        script.add_keyboard_action(ActionType::Press, Keycode::A);
+        clock.advance(10_000);
+        script.add_keyboard_action(ActionType::Release, Keycode::A);
+
+        let sink = MockSink::new();
+        let mut actor = Actor::new(script, false, ActionSense::Both, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+
+        let handle = actor.act(None).unwrap();
+        while sink.dispatched().is_empty() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        thread::sleep(Duration::from_millis(50));
+
+        let progress = handle.progress();
+        assert_eq!((progress.cursor, progress.iteration, progress.duration), (1, 0, 10_000));
+        assert!(progress.elapsed >= 50 && progress.elapsed < 1000);
+        assert!(handle.is_working());
+
+        // the held key is released once stopped
+        handle.stop();
+        handle.join();
+        assert!(!actor.is_working());
+        assert_eq!(sink.dispatched().len(), 2);
+    }
+
+    /// A sink that calls the hook (in the acting thread) once a key is pressed
+    struct HookedSink {
+        inner: MockSink,
+        hook: Box<dyn FnMut(CanonicalKey) + Send>,
+    }
+
+    impl HookedSink {
+        /// Create a sink pressing the signal (on the source) once the given key is pressed for the first time
+        fn signaling(inner: MockSink, source: MockSource, on: CanonicalKey, signal: CanonicalKey) -> HookedSink {
+            let mut fired = false;
+            HookedSink {
+                inner,
+                hook: Box::new(move |key| {
+                    if key == on && !fired {
+                        fired = true;
+                        source.emit(InputEvent::KeyDown(signal));
+                    }
+                }),
+            }
+        }
+    }
+
+    impl EventSink for HookedSink {
+        fn key_down(&mut self, key: CanonicalKey) {
+            self.inner.key_down(key);
+            (self.hook)(key);
+        }
+
+        fn key_up(&mut self, key: CanonicalKey) {
+            self.inner.key_up(key);
+        }
+
+        fn mouse_down(&mut self, button: CanonicalButton) {
+            self.inner.mouse_down(button);
+        }
+
+        fn mouse_up(&mut self, button: CanonicalButton) {
+            self.inner.mouse_up(button);
+        }
+
+        fn mouse_move_to(&mut self, pos: (i32, i32)) {
+            self.inner.mouse_move_to(pos);
+        }
+
+        fn mouse_scroll(&mut self, delta: (i32, i32)) {
+            self.inner.mouse_scroll(delta);
+        }
+
+        fn type_text(&mut self, text: &str) {
+            self.inner.type_text(text);
+        }
+    }
+
+    #[test]
+    fn act_pause() {
+        let clock = VirtualClock::new(0);
+        let source = MockSource::new();
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(MOCK_SCRIPT_RAW).unwrap(), false, ActionSense::Both, Some(CanonicalKey::Escape.into()));
+        actor.set_pause_signal(Some(CanonicalKey::Pause.into()));
+        actor.set_source(Arc::new(source.clone()));
+        actor.set_clock(Arc::new(clock.clone()));
+        // pause right after 'KeyA' is pressed
+        actor.set_sink(Arc::new(Mutex::new(HookedSink::signaling(sink.clone(), source.clone(), CanonicalKey::KeyA, CanonicalKey::Pause))));
+
+        let handle = actor.act(None).unwrap();
+
+        // the held key is released once paused
+        while sink.dispatched().len() < 2 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert!(handle.is_paused());
+        clock.advance(1000);
+        assert_eq!(handle.progress().elapsed, 10);
+
+        // the held key is pressed again once resumed, and the rest goes on with the same gaps
+        source.emit(InputEvent::KeyDown(CanonicalKey::Pause));
+        handle.join();
+        assert_eq!(sink.dispatched_at(), vec![
+            (10, InputEvent::KeyDown(CanonicalKey::KeyA)),
+            (10, InputEvent::KeyUp(CanonicalKey::KeyA)),
+            (1010, InputEvent::KeyDown(CanonicalKey::KeyA)),
+            (1020, InputEvent::MouseMove((30, 40))),
+            (1030, InputEvent::MouseDown(CanonicalButton::Left, (30, 40))),
+            (1040, InputEvent::MouseUp(CanonicalButton::Left, (30, 40))),
+            (1050, InputEvent::KeyUp(CanonicalKey::KeyA)),
+        ]);
+        assert_eq!(clock.now(), 1060);
+
+        // a paused acting can be stopped
+        sink.clear();
+        actor.set_sink(Arc::new(Mutex::new(HookedSink::signaling(sink.clone(), source.clone(), CanonicalKey::KeyA, CanonicalKey::Pause))));
+        let handle = actor.act(None).unwrap();
+        while sink.dispatched().len() < 2 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        actor.finish();
+        handle.join();
+        assert!(!actor.is_working());
+        assert_eq!(sink.dispatched().len(), 2);
+    }
+
+    #[test]
+    fn act_start() {
+        let clock = VirtualClock::new(0);
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(MOCK_SCRIPT_RAW).unwrap(), false, ActionSense::Both, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        // start in the middle of a drag with 'KeyA' held
+        actor.set_start(Offset::Time(35));
+        actor.act_sync().unwrap();
+        assert_eq!(sink.dispatched_at(), vec![
+            (0, InputEvent::KeyDown(CanonicalKey::KeyA)),
+            (0, InputEvent::MouseMove((30, 40))),
+            (0, InputEvent::MouseDown(CanonicalButton::Left, (30, 40))),
+            (5, InputEvent::MouseUp(CanonicalButton::Left, (30, 40))),
+            (15, InputEvent::KeyUp(CanonicalKey::KeyA)),
+        ]);
+        assert_eq!(clock.now(), 25);
+
+        // start at an action, the following loops start from the beginning
+        sink.clear();
+        actor.set_start(Offset::Action(3));
+        actor.set_repeat(Repeat::Times(2));
+        actor.act_sync().unwrap();
+        let times: Vec<i64> = sink.dispatched_at().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(times, vec![25, 25, 25, 25, 35, 55, 65, 75, 85, 95]);
+        assert_eq!(clock.now(), 105);
+    }
+
+    #[test]
+    fn act_seek() {
+        let clock = VirtualClock::new(0);
+        let source = MockSource::new();
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(MOCK_SCRIPT_RAW).unwrap(), false, ActionSense::Both, Some(CanonicalKey::Escape.into()));
+        actor.set_pause_signal(Some(CanonicalKey::Pause.into()));
+        actor.set_source(Arc::new(source.clone()));
+        actor.set_clock(Arc::new(clock.clone()));
+        // pause right after 'KeyA' is pressed
+        actor.set_sink(Arc::new(Mutex::new(HookedSink::signaling(sink.clone(), source.clone(), CanonicalKey::KeyA, CanonicalKey::Pause))));
+
+        // only the paused acting can be moved
+        assert_eq!(actor.seek(Offset::Time(45)).unwrap_err(), TapeError::NotPaused);
+
+        let handle = actor.act(None).unwrap();
+        while sink.dispatched().len() < 2 {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // the progress follows the seeking at once
+        handle.seek(Offset::Time(45)).unwrap();
+        clock.advance(1000);
+        assert_eq!(handle.progress(), Progress { cursor: 4, elapsed: 45, iteration: 0, duration: 60 });
+
+        // what is held there is restored once resumed
+        handle.resume();
+        handle.join();
+        assert_eq!(sink.dispatched_at(), vec![
+            (10, InputEvent::KeyDown(CanonicalKey::KeyA)),
+            (10, InputEvent::KeyUp(CanonicalKey::KeyA)),
+            (1010, InputEvent::MouseMove((30, 40))),
+            (1010, InputEvent::KeyDown(CanonicalKey::KeyA)),
+            (1015, InputEvent::KeyUp(CanonicalKey::KeyA)),
+        ]);
+        assert_eq!(clock.now(), 1025);
+    }
+
+    #[test]
+    fn act_pseudo_actions() {
+        let raw = r##"
+name = "pseudo"
+ctime = 1695739924720
+duration = 40
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action]
+Keyboard = ["Press", "KeyA"]
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action]
+Marker = "login done"
+
+[[actions]]
+ctime = 1695739924740
+timeline = 20
+
+[actions.action]
+Comment = "give the page some time"
+
+[[actions]]
+ctime = 1695739924740
+timeline = 20
+
+[actions.action]
+Wait = 100
+
+[[actions]]
+ctime = 1695739924750
+timeline = 30
+
+[actions.action]
+Keyboard = ["Release", "KeyA"]
+        "##;
+        let script = Script::load(raw).unwrap();
+        assert_eq!(script.to_filtered(ActionSense::Mouse).actions.len(), 3);
+
+        let clock = VirtualClock::new(0);
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(script, false, ActionSense::Keyboard, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        // nothing is dispatched by the pseudo-actions, the wait delays the rest
+        actor.act_sync().unwrap();
+        assert_eq!(sink.dispatched_at(), vec![
+            (10, InputEvent::KeyDown(CanonicalKey::KeyA)),
+            (130, InputEvent::KeyUp(CanonicalKey::KeyA)),
+        ]);
+        assert_eq!(clock.now(), 140);
+
+        // the wait is scaled by the speed like the others
+        sink.clear();
+        actor.set_speed(2.0).unwrap();
+        actor.act_sync().unwrap();
+        let times: Vec<i64> = sink.dispatched_at().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(times, vec![145, 205]);
+        assert_eq!(clock.now(), 210);
+
+        // a negative wait fails the self-check
+        let raw = raw.replace("Wait = 100", "Wait = -100");
+        assert!(matches!(Script::load(&raw).unwrap_err(), TapeError::SelfCheck { index: 3, .. }));
+    }
+
+    #[test]
+    fn act_text() {
+        let raw = r##"
+name = "text"
+ctime = 1695739924720
+duration = 30
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action.Text]
+text = "café"
+delay = 5
+
+[[actions]]
+ctime = 1695739924750
+timeline = 30
+
+[actions.action.Text]
+text = "東京"
+        "##;
+        let clock = VirtualClock::new(0);
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(raw).unwrap(), false, ActionSense::Keyboard, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        // typed character by character with the delay, or at once without it
+        actor.act_sync().unwrap();
+        assert_eq!(sink.dispatched_at(), vec![
+            (10, InputEvent::Text("c".to_string())),
+            (15, InputEvent::Text("a".to_string())),
+            (20, InputEvent::Text("f".to_string())),
+            (25, InputEvent::Text("é".to_string())),
+            (30, InputEvent::Text("東京".to_string())),
+        ]);
+
+        // the text goes with the keyboard
+        sink.clear();
+        actor.set_act_type(ActionSense::Mouse);
+        actor.act_sync().unwrap();
+        assert!(sink.dispatched().is_empty());
+    }
+
+    #[test]
+    fn act_release_held() {
+        // a script ending with 'KeyA' and the left button held
+        let clock = VirtualClock::new(0);
+        let mut script = Script::with_clock(Arc::new(clock.clone()));
+        clock.advance(10);
+        script.add_keyboard_action(ActionType::Press, Keycode::A);
+        script.add_mouse_action(ActionType::Press, 1, (5, 5));
+        clock.advance(10);
+        script.bound();
+
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(script, false, ActionSense::Both, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        // released (in reverse order) at the end of every loop
+        actor.set_repeat(Repeat::Times(2));
+        actor.act_sync().unwrap();
+        let released: Vec<(i64, InputEvent)> = sink.dispatched_at().into_iter()
+            .filter(|(_, ev)| matches!(ev, InputEvent::KeyUp(_) | InputEvent::MouseUp(..)))
+            .collect();
+        assert_eq!(released, vec![
+            (40, InputEvent::MouseUp(CanonicalButton::Left, (0, 0))),
+            (40, InputEvent::KeyUp(CanonicalKey::KeyA)),
+            (60, InputEvent::MouseUp(CanonicalButton::Left, (0, 0))),
+            (60, InputEvent::KeyUp(CanonicalKey::KeyA)),
+        ]);
+
+        // released even if the sink panics, and the actor is not left working
+        sink.clear();
+        actor.set_repeat(Repeat::Once);
+        actor.set_sink(Arc::new(Mutex::new(HookedSink {
+            inner: sink.clone(),
+            hook: Box::new(|_| panic!("the device is gone")),
+        })));
+        actor.act(None).unwrap().join();
+        assert!(!actor.is_working());
+        assert_eq!(sink.dispatched(), vec![
+            InputEvent::KeyDown(CanonicalKey::KeyA),
+            InputEvent::KeyUp(CanonicalKey::KeyA),
+        ]);
+    }
+
+    #[test]
+    fn act_chord_release_held() {
+        let raw = r##"
+name = "chord"
+ctime = 1695739924720
+duration = 20
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action.Chord]
+modifiers = ["LCtrl", "LShift"]
+key = "KeyT"
+        "##;
+        let clock = VirtualClock::new(0);
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(raw).unwrap(), false, ActionSense::Keyboard, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        // the sink panics once the key is pressed, with the modifiers still held
+        actor.set_sink(Arc::new(Mutex::new(HookedSink {
+            inner: sink.clone(),
+            hook: Box::new(|key| if key == CanonicalKey::KeyT { panic!("the device is gone") }),
+        })));
+        actor.act(None).unwrap().join();
+        assert!(!actor.is_working());
+
+        // what is pressed so far is released (in reverse order)
+        assert_eq!(sink.dispatched(), vec![
+            InputEvent::KeyDown(CanonicalKey::LCtrl),
+            InputEvent::KeyDown(CanonicalKey::LShift),
+            InputEvent::KeyDown(CanonicalKey::KeyT),
+            InputEvent::KeyUp(CanonicalKey::KeyT),
+            InputEvent::KeyUp(CanonicalKey::LShift),
+            InputEvent::KeyUp(CanonicalKey::LCtrl),
+        ]);
+    }
+
+    #[test]
+    fn act_speed() {
+        let clock = VirtualClock::new(0);
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(MOCK_SCRIPT_RAW).unwrap(), false, ActionSense::Both, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        // every wait is scaled, including the trailing duration
+        actor.set_speed(2.0).unwrap();
+        actor.act_sync().unwrap();
+        let times: Vec<i64> = sink.dispatched_at().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(times, vec![5, 10, 15, 20, 25]);
+        assert_eq!(clock.now(), 30);
+
+        sink.clear();
+        actor.set_speed(0.5).unwrap();
+        actor.act_sync().unwrap();
+        let times: Vec<i64> = sink.dispatched_at().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(times, vec![50, 70, 90, 110, 130]);
+        assert_eq!(clock.now(), 150);
+
+        assert_eq!(actor.set_speed(0.0).unwrap_err(), TapeError::InvalidSpeed(0.0));
+        assert!(actor.set_speed(f64::NAN).is_err());
+        assert_eq!(actor.speed(), 0.5);
+    }
+
+    #[test]
+    fn act_speed_change() {
+        let clock = VirtualClock::new(0);
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(MOCK_SCRIPT_RAW).unwrap(), false, ActionSense::Both, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        // speed up right after 'KeyA' is pressed
+        let handle: Arc<Mutex<Option<PlaybackHandle>>> = Arc::new(Mutex::new(None));
+        let tmp1 = Arc::clone(&handle);
+        actor.set_sink(Arc::new(Mutex::new(HookedSink {
+            inner: sink.clone(),
+            hook: Box::new(move |key| {
+                if key == CanonicalKey::KeyA {
+                    while tmp1.lock().unwrap().is_none() {
+                        thread::yield_now();
+                    }
+                    tmp1.lock().unwrap().as_ref().unwrap().set_speed(2.0).unwrap();
+                }
+            }),
+        })));
+
+        *handle.lock().unwrap() = Some(actor.act(None).unwrap());
+        while actor.is_working() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // the rest goes on from where it is, without a jump
+        let times: Vec<i64> = sink.dispatched_at().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(times, vec![10, 15, 20, 25, 30]);
+        assert_eq!(clock.now(), 35);
+        assert_eq!(handle.lock().unwrap().as_ref().unwrap().progress().elapsed, 60);
+    }
+
+    #[test]
+    fn act_repeat() {
+        let clock = VirtualClock::new(0);
+        let sink = MockSink::with_clock(Arc::new(clock.clone()));
+        let mut actor = Actor::new(Script::load(MOCK_SCRIPT_RAW).unwrap(), false, ActionSense::Keyboard, None);
+        actor.set_source(Arc::new(MockSource::new()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+        actor.set_clock(Arc::new(clock.clone()));
+
+        // three loops with a delay between them
+        actor.set_repeat(Repeat::Times(3));
+        actor.set_loop_delay(100);
+        let loops = Arc::new(Mutex::new(vec![]));
+        let tmp1 = Arc::clone(&loops);
+        let tmp2 = clock.clone();
+        let handle = actor.act(Some(Box::new(move || tmp1.lock().unwrap().push(tmp2.now())))).unwrap();
+        handle.join();
+
+        assert_eq!(*loops.lock().unwrap(), vec![60, 220, 380]);
+        let times: Vec<i64> = sink.dispatched_at().into_iter().map(|(t, _)| t).collect();
+        assert_eq!(times, vec![10, 50, 170, 210, 330, 370]);
+        assert_eq!(last_iteration(&actor), 2);
+
+        // loops begin until the deadline, the last one is performed to the end
+        sink.clear();
+        actor.set_loop_delay(0);
+        actor.set_repeat(Repeat::Until(clock.now() + 100));
+        actor.act_sync().unwrap();
+        assert_eq!(sink.dispatched().len(), 4);
+        assert_eq!(clock.now(), 380 + 120);
+
+        // nothing to act
+        sink.clear();
+        actor.set_repeat(Repeat::Times(0));
+        actor.act_sync().unwrap();
+        assert!(sink.dispatched().is_empty());
+
+        // only the infinite acting needs a stop signal
+        actor.set_repeat(Repeat::Forever);
+        assert_eq!(actor.act_sync().unwrap_err(), TapeError::NoStopSignal);
+    }
+
+    /// The iteration of the last acting of the actor
+    fn last_iteration(actor: &Actor) -> usize {
+        actor.playback.lock().unwrap().iteration
+    }
+
+    #[test]
+    fn act_mock_stop_signal() {
+        let script = Script::load(MOCK_SCRIPT_RAW).unwrap();
+        let source = MockSource::new();
+        let sink = MockSink::new();
+        let mut actor = Actor::new(script, true, ActionSense::Both, Some(CanonicalKey::Escape.into()));
+        actor.set_source(Arc::new(source.clone()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+
+        actor.act(None).unwrap();
+        wait_for(|| sink.dispatched().len() > 5);
+        assert!(actor.is_working());
+        assert_eq!(actor.act(None).unwrap_err(), TapeError::AlreadyWorking);
+        assert_eq!(actor.act_sync().unwrap_err(), TapeError::AlreadyWorking);
+
+        // the cyclic acting only stops on the stop signal
+        source.emit(InputEvent::KeyDown(CanonicalKey::Escape));
+        wait_for(|| !actor.is_working() && !source.is_listened());
+    }
+
+    #[test]
+    fn act_chord() {
+        let raw = r##"
+name = "chord"
+ctime = 1695739924720
+duration = 20
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action.Chord]
+modifiers = ["LCtrl", "LShift"]
+key = "KeyT"
+        "##;
+        let source = MockSource::new();
+        let sink = MockSink::new();
+        let mut actor = Actor::new(Script::load(raw).unwrap(), true, ActionSense::Keyboard, Some("ctrl+shift+q".parse().unwrap()));
+        actor.set_source(Arc::new(source.clone()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+
+        // the chord is expanded into nested presses and releases
+        let handle = actor.act(None).unwrap();
+        while sink.dispatched().len() < 6 {
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(sink.dispatched()[..6], [
+            InputEvent::KeyDown(CanonicalKey::LCtrl),
+            InputEvent::KeyDown(CanonicalKey::LShift),
+            InputEvent::KeyDown(CanonicalKey::KeyT),
+            InputEvent::KeyUp(CanonicalKey::KeyT),
+            InputEvent::KeyUp(CanonicalKey::LShift),
+            InputEvent::KeyUp(CanonicalKey::LCtrl),
+        ]);
+
+        // the key alone is not the stop signal
+        source.emit(InputEvent::KeyDown(CanonicalKey::KeyQ));
+        source.emit(InputEvent::KeyUp(CanonicalKey::KeyQ));
+        thread::sleep(Duration::from_millis(30));
+        assert!(handle.is_working());
+
+        // either side of the modifiers counts
+        source.emit(InputEvent::KeyDown(CanonicalKey::RCtrl));
+        source.emit(InputEvent::KeyDown(CanonicalKey::LShift));
+        source.emit(InputEvent::KeyDown(CanonicalKey::KeyQ));
+        handle.join();
+        assert!(!actor.is_working());
+    }
+
+    #[test]
+    fn act_stop_latency() {
+        // a script with a long gap between the actions
+        let clock = VirtualClock::new(0);
+        let mut script = Script::with_clock(Arc::new(clock.clone()));
+        script.add_keyboard_action(ActionType::Press, Keycode::A);
+        clock.advance(60_000);
+        script.add_keyboard_action(ActionType::Release, Keycode::A);
+
+        let source = MockSource::new();
+        let sink = MockSink::new();
+        let mut actor = Actor::new(script, false, ActionSense::Both, Some(CanonicalKey::Escape.into()));
+        actor.set_source(Arc::new(source.clone()));
+        actor.set_sink(Arc::new(Mutex::new(sink.clone())));
+
+        let handle = actor.act(None).unwrap();
+        while sink.dispatched().is_empty() {
+            thread::sleep(Duration::from_millis(1));
+        }
+
+        // the acting stops in the middle of the gap, without waiting for it to pass
+        source.emit(InputEvent::KeyDown(CanonicalKey::Escape));
+        while handle.is_working() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        let progress = handle.progress();
+        assert_eq!(progress.cursor, 1);
+        assert!(progress.elapsed < 30_000);
+        assert_eq!(sink.dispatched(), vec![
+            InputEvent::KeyDown(CanonicalKey::KeyA),
+            // the held key is released once stopped
+            InputEvent::KeyUp(CanonicalKey::KeyA),
+        ]);
+
+        // so does the interruption by 'finish'
+        let handle = actor.act(None).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        actor.finish();
+        while handle.is_working() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        let progress = handle.progress();
+        assert_eq!(progress.cursor, 1);
+        assert!(progress.elapsed < 30_000);
+    }
 
     #[test]
     fn act() {
@@ -497,12 +1922,12 @@ Keyboard = ["Press", "KeyI"]
         // endregion
 
         let script = Script::load(script_raw).unwrap();
-        let mut actor = Actor::new(script, false, ActionSense::Keyboard, Some(CanonicalKey::Escape));
+        let mut actor = Actor::new(script, false, ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
 
         // sleep 3 seconds for the user to prepare
         thread::sleep(Duration::from_secs(3));
 
-        actor.act(None);
+        actor.act(None).unwrap();
 
         // sleep 8 seconds for the actor to preform
         thread::sleep(Duration::from_secs(8));
@@ -696,7 +2121,7 @@ Keyboard = ["Press", "KeyI"]
         // endregion
 
         let script = Script::load(script_raw).unwrap();
-        let mut actor = Actor::new(script, false, ActionSense::Keyboard, Some(CanonicalKey::Escape));
+        let mut actor = Actor::new(script, false, ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
 
         // sleep 3 seconds for the user to prepare
         thread::sleep(Duration::from_secs(3));
@@ -712,20 +2137,25 @@ Keyboard = ["Press", "KeyI"]
 
     #[test]
     fn next_action_test() {
-        let mut mv = Script::empty();
-        thread::sleep(Duration::from_secs(1));
+        let clock = VirtualClock::new(0);
+        let mut mv = Script::with_clock(Arc::new(clock.clone()));
+        clock.advance(1000);
         mv.add_keyboard_action(ActionType::Press, Keycode::A);
-        thread::sleep(Duration::from_secs(2));
+        clock.advance(2000);
         mv.add_keyboard_action(ActionType::Release, Keycode::K);
-        thread::sleep(Duration::from_secs(1));
+        clock.advance(1000);
         mv.add_mouse_action(ActionType::Press, 1, (50, 50));
-        thread::sleep(Duration::from_secs(1));
+        clock.advance(1000);
         mv.add_mouse_action(ActionType::Release, 0, (50, 50));
 
+        let mut timelines = vec![];
         while let Some(action) = mv.next_action() {
-            println!("next action is [{:?}]", action);
-            thread::sleep(Duration::from_secs(1));
+            timelines.push(action.timeline);
         }
+        assert_eq!(timelines, vec![1000, 3000, 4000, 5000]);
+
+        mv.reset_cursor();
+        assert_eq!(mv.next_action().unwrap().timeline, 1000);
     }
 
     #[test]
@@ -0,0 +1,159 @@
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use crate::backend::{EventCallback, EventSink, EventSource, InputEvent, ListenGuard};
+use crate::canonicalize::declaration::{CanonicalButton, CanonicalKey};
+use crate::clock::{Clock, default_clock};
+
+type WeakCallback = Weak<dyn Fn(&InputEvent) + Send + Sync>;
+
+/// An in-memory [EventSource](../trait.EventSource.html) for tests.
+///
+/// - Use [emit](#method.emit) to feed an event to the current listeners.
+/// - Use [scripted](#method.scripted) to feed a sequence of events (in another thread) as soon as someone listens.
+#[derive(Clone, Default)]
+pub struct MockSource {
+    /// The listeners, removed once their guards are dropped
+    listeners: Arc<Mutex<Vec<WeakCallback>>>,
+    /// The events to be fed to the next listener
+    pending: Arc<Mutex<Vec<InputEvent>>>,
+}
+
+impl MockSource {
+    /// Create a source without any event
+    pub fn new() -> MockSource {
+        MockSource::default()
+    }
+
+    /// Create a source that feeds the events (in order) once it is listened to
+    pub fn scripted(events: Vec<InputEvent>) -> MockSource {
+        MockSource {
+            listeners: Arc::new(Mutex::new(Vec::new())),
+            pending: Arc::new(Mutex::new(events)),
+        }
+    }
+
+    /// Whether someone is listening to the source
+    pub fn is_listened(&self) -> bool {
+        self.listeners.lock().unwrap().iter().any(|l| l.strong_count() > 0)
+    }
+
+    /// Feed an event to all the listeners (in the current thread)
+    pub fn emit(&self, event: InputEvent) {
+        // collect the living listeners first, so that a listener can drop its guard when being called
+        let living: Vec<EventCallback> = {
+            let mut listeners = self.listeners.lock().unwrap();
+            listeners.retain(|l| l.strong_count() > 0);
+            listeners.iter().filter_map(|l| l.upgrade()).collect()
+        };
+
+        for callback in living {
+            callback(&event);
+        }
+    }
+}
+
+impl EventSource for MockSource {
+    fn listen(&self, callback: EventCallback) -> ListenGuard {
+        self.listeners.lock().unwrap().push(Arc::downgrade(&callback));
+
+        // feed the scripted events (if any) like a real device does -- from another thread
+        let events: Vec<InputEvent> = self.pending.lock().unwrap().drain(..).collect();
+        if !events.is_empty() {
+            let source = self.clone();
+            thread::spawn(move || {
+                for event in events {
+                    source.emit(event);
+                }
+            });
+        }
+
+        Box::new(callback)
+    }
+}
+
+/// An in-memory [EventSink](../trait.EventSink.html) for tests, it remembers everything dispatched to it (and when).
+///
+/// The position of mouse press/release is the last position the mouse moved to.
+#[derive(Clone)]
+pub struct MockSink {
+    /// The events dispatched to the sink, with the time they are dispatched
+    dispatched: Arc<Mutex<Vec<(i64, InputEvent)>>>,
+    /// The current position of the mouse
+    position: Arc<Mutex<(i32, i32)>>,
+    /// The clock used to stamp the events
+    clock: Arc<dyn Clock>,
+}
+
+impl Default for MockSink {
+    fn default() -> Self {
+        MockSink::with_clock(default_clock())
+    }
+}
+
+impl MockSink {
+    /// Create an empty sink
+    pub fn new() -> MockSink {
+        MockSink::default()
+    }
+
+    /// Create an empty sink stamping the events with the given clock
+    pub fn with_clock(clock: Arc<dyn Clock>) -> MockSink {
+        MockSink {
+            dispatched: Arc::new(Mutex::new(Vec::new())),
+            position: Arc::new(Mutex::new((0, 0))),
+            clock,
+        }
+    }
+
+    /// Get a copy of the events dispatched so far
+    pub fn dispatched(&self) -> Vec<InputEvent> {
+        self.dispatched.lock().unwrap().iter().map(|(_, ev)| ev.clone()).collect()
+    }
+
+    /// Get a copy of the events dispatched so far, with the time (of the clock) they are dispatched
+    pub fn dispatched_at(&self) -> Vec<(i64, InputEvent)> {
+        self.dispatched.lock().unwrap().clone()
+    }
+
+    /// Forget the events dispatched so far
+    pub fn clear(&self) {
+        self.dispatched.lock().unwrap().clear();
+    }
+
+    fn push(&self, event: InputEvent) {
+        self.dispatched.lock().unwrap().push((self.clock.now(), event));
+    }
+}
+
+impl EventSink for MockSink {
+    fn key_down(&mut self, key: CanonicalKey) {
+        self.push(InputEvent::KeyDown(key));
+    }
+
+    fn key_up(&mut self, key: CanonicalKey) {
+        self.push(InputEvent::KeyUp(key));
+    }
+
+    fn mouse_down(&mut self, button: CanonicalButton) {
+        let pos = *self.position.lock().unwrap();
+        self.push(InputEvent::MouseDown(button, pos));
+    }
+
+    fn mouse_up(&mut self, button: CanonicalButton) {
+        let pos = *self.position.lock().unwrap();
+        self.push(InputEvent::MouseUp(button, pos));
+    }
+
+    fn mouse_move_to(&mut self, pos: (i32, i32)) {
+        *self.position.lock().unwrap() = pos;
+        self.push(InputEvent::MouseMove(pos));
+    }
+
+    fn mouse_scroll(&mut self, delta: (i32, i32)) {
+        self.push(InputEvent::MouseScroll(delta));
+    }
+
+    fn type_text(&mut self, text: &str) {
+        self.push(InputEvent::Text(text.to_string()));
+    }
+}
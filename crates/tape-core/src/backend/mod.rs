@@ -0,0 +1,116 @@
+use std::sync::{Arc, Mutex};
+use crate::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalButton, CanonicalKey};
+
+pub mod native;
+pub mod mock;
+
+/// An **input event** is something that happened on the keyboard or the mouse
+#[derive(Debug, Clone, PartialEq)]
+pub enum InputEvent {
+    /// A key is pressed
+    KeyDown(CanonicalKey),
+    /// A key is released
+    KeyUp(CanonicalKey),
+    /// A mouse button is pressed -- Tuple(button, pos)
+    MouseDown(CanonicalButton, (i32, i32)),
+    /// A mouse button is released -- Tuple(button, pos)
+    MouseUp(CanonicalButton, (i32, i32)),
+    /// The mouse is moved to a position
+    MouseMove((i32, i32)),
+    /// The mouse wheel is scrolled -- Tuple(delta), the steps scrolled horizontally and vertically (positive for right/down)
+    MouseScroll((i32, i32)),
+    /// A text is typed (only dispatched to the sinks, never reported by the sources)
+    Text(String),
+}
+
+/// The callback invoked by an [EventSource](trait.EventSource.html) for every event it receives
+pub type EventCallback = Arc<dyn Fn(&InputEvent) + Send + Sync>;
+
+/// A guard returned by [listen](trait.EventSource.html#tymethod.listen), the listener is removed once it is dropped
+pub type ListenGuard = Box<dyn Send>;
+
+/// The keys held down on a source, tracked from its events (to tell the signal [chord](../canonicalize/declaration/struct.CanonicalChord.html)s)
+#[derive(Debug, Default)]
+pub(crate) struct HeldKeys(Mutex<Vec<CanonicalKey>>);
+
+impl HeldKeys {
+    /// Track the event, return the keys held down at the moment (including the one just pressed or released)
+    pub(crate) fn track(&self, ev: &InputEvent) -> Vec<CanonicalKey> {
+        let mut held = self.0.lock().unwrap();
+        match *ev {
+            InputEvent::KeyDown(key) => {
+                if !held.contains(&key) { held.push(key); }
+                held.clone()
+            }
+            InputEvent::KeyUp(key) => {
+                let snapshot = held.clone();
+                held.retain(|v| *v != key);
+                snapshot
+            }
+            _ => held.clone(),
+        }
+    }
+}
+
+/// An **event source** is where a [recorder](../record/struct.Recorder.html) gets the events from.
+///
+/// (The [actor](../act/struct.Actor.html) also listens to it for the stop signal)
+///
+/// Not every source reports the mouse wheel, the ones supporting it report [InputEvent::MouseScroll](enum.InputEvent.html#variant.MouseScroll).
+pub trait EventSource: Send + Sync {
+    /// Register a listener for all the events of the source.
+    ///
+    /// The callback may be invoked from another thread, and will not be invoked anymore once the guard is dropped.
+    fn listen(&self, callback: EventCallback) -> ListenGuard;
+}
+
+/// An **event sink** is where an [actor](../act/struct.Actor.html) dispatches the events to.
+pub trait EventSink: Send {
+    /// Press a key
+    fn key_down(&mut self, key: CanonicalKey);
+
+    /// Release a key
+    fn key_up(&mut self, key: CanonicalKey);
+
+    /// Press a mouse button (at the current position)
+    fn mouse_down(&mut self, button: CanonicalButton);
+
+    /// Release a mouse button (at the current position)
+    fn mouse_up(&mut self, button: CanonicalButton);
+
+    /// Move the mouse to a position
+    fn mouse_move_to(&mut self, pos: (i32, i32));
+
+    /// Scroll the mouse wheel by the steps horizontally and vertically (positive for right/down)
+    fn mouse_scroll(&mut self, delta: (i32, i32));
+
+    /// Type a text (any unicode, regardless of the keyboard layout)
+    fn type_text(&mut self, text: &str);
+
+    /// Perform an action
+    fn perform(&mut self, action: &CanonicalAction) {
+        match *action {
+            CanonicalAction::Keyboard(t, k) => match t {
+                ActionType::Press => self.key_down(k),
+                ActionType::Release => self.key_up(k),
+                // ignore ActionType::Move cause it is not a valid keyboard action type
+                ActionType::Move => {}
+            }
+            CanonicalAction::Mouse(t, b, p) => match t {
+                ActionType::Press => self.mouse_down(b),
+                ActionType::Release => self.mouse_up(b),
+                ActionType::Move => self.mouse_move_to(p),
+            }
+            CanonicalAction::Chord(ref chord) => {
+                for action in chord.actions() {
+                    self.perform(&action);
+                }
+            }
+            CanonicalAction::Scroll(d) => self.mouse_scroll(d),
+            // the delay between the characters is up to the actor, the text is typed at once here
+            CanonicalAction::Text { ref text, .. } => self.type_text(text),
+            // pseudo-actions dispatch no input (the explicit wait is up to the actor)
+            CanonicalAction::Marker(_) | CanonicalAction::Comment(_) | CanonicalAction::Wait(_) => {}
+        }
+    }
+}
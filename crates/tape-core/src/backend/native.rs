@@ -0,0 +1,107 @@
+use std::sync::Arc;
+use device_query::{DeviceEvents, DeviceQuery, DeviceState};
+use enigo::{Enigo, KeyboardControllable, MouseControllable};
+use crate::backend::{EventCallback, EventSink, EventSource, InputEvent, ListenGuard};
+use crate::canonicalize::declaration::{CanonicalButton, CanonicalKey};
+
+/// The default [EventSource](../trait.EventSource.html), listening to the devices with `device_query`
+///
+/// The mouse wheel is not reported, since `device_query` does not support it.
+#[derive(Debug, Clone, Default)]
+pub struct DeviceSource;
+
+impl EventSource for DeviceSource {
+    fn listen(&self, callback: EventCallback) -> ListenGuard {
+        let ds = DeviceState::new();
+
+        // keydown listener
+        let tmp1 = Arc::clone(&callback);
+        let guard_kd = ds.on_key_down(move |key| {
+            tmp1(&InputEvent::KeyDown((*key).into()));
+        });
+
+        // keyup listener
+        let tmp1 = Arc::clone(&callback);
+        let guard_ku = ds.on_key_up(move |key| {
+            tmp1(&InputEvent::KeyUp((*key).into()));
+        });
+
+        // mousedown listener
+        let tmp1 = Arc::clone(&callback);
+        let tmp2 = DeviceState::new();
+        let guard_md = ds.on_mouse_down(move |btn| {
+            tmp1(&InputEvent::MouseDown((*btn).into(), tmp2.get_mouse().coords));
+        });
+
+        // mouseup listener
+        let tmp1 = Arc::clone(&callback);
+        let tmp2 = DeviceState::new();
+        let guard_mu = ds.on_mouse_up(move |btn| {
+            tmp1(&InputEvent::MouseUp((*btn).into(), tmp2.get_mouse().coords));
+        });
+
+        // mousemove listener
+        let tmp1 = Arc::clone(&callback);
+        let guard_mm = ds.on_mouse_move(move |pos| {
+            tmp1(&InputEvent::MouseMove(*pos));
+        });
+
+        Box::new((guard_kd, guard_ku, guard_md, guard_mu, guard_mm))
+    }
+}
+
+/// The default [EventSink](../trait.EventSink.html), simulating the devices with `enigo`
+///
+/// Keys and buttons that cannot be simulated are ignored.
+pub struct EnigoSink {
+    eg: Enigo,
+}
+
+impl Default for EnigoSink {
+    fn default() -> Self {
+        EnigoSink { eg: Enigo::new() }
+    }
+}
+
+impl EventSink for EnigoSink {
+    fn key_down(&mut self, key: CanonicalKey) {
+        if let Ok(enigo_key) = key.try_into() {
+            self.eg.key_down(enigo_key);
+        }
+    }
+
+    fn key_up(&mut self, key: CanonicalKey) {
+        if let Ok(enigo_key) = key.try_into() {
+            self.eg.key_up(enigo_key);
+        }
+    }
+
+    fn mouse_down(&mut self, button: CanonicalButton) {
+        if let Ok(enigo_button) = button.try_into() {
+            self.eg.mouse_down(enigo_button);
+        }
+    }
+
+    fn mouse_up(&mut self, button: CanonicalButton) {
+        if let Ok(enigo_button) = button.try_into() {
+            self.eg.mouse_up(enigo_button);
+        }
+    }
+
+    fn mouse_move_to(&mut self, pos: (i32, i32)) {
+        self.eg.mouse_move_to(pos.0, pos.1);
+    }
+
+    fn mouse_scroll(&mut self, delta: (i32, i32)) {
+        if delta.0 != 0 {
+            self.eg.mouse_scroll_x(delta.0);
+        }
+        if delta.1 != 0 {
+            self.eg.mouse_scroll_y(delta.1);
+        }
+    }
+
+    fn type_text(&mut self, text: &str) {
+        self.eg.key_sequence(text);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::canonicalize::{Action, Script};
+
+/// Put the action at the point of the timeline of the script (restamped by the creation of the script)
+fn place(script: &Script, action: &Action, offset: i64) -> Action {
+    let timeline = action.timeline + offset;
+    Action { ctime: script.ctime + timeline, timeline, action: action.action.clone() }
+}
+
+// Collection of methods of Script on 'compose'
+impl Script {
+    /// Append the other script after this one, `gap` milliseconds after the end of this one (negative is taken as 0).
+    ///
+    /// The actions appended are restamped by this script, and the duration lasts until the end of the other one.
+    pub fn append(&mut self, other: &Script, gap: i64) {
+        let offset = self.duration + gap.max(0);
+        let appended: Vec<Action> = other.actions.iter().map(|v| place(self, v, offset)).collect();
+
+        self.actions.extend(appended);
+        self.duration = offset + other.duration;
+    }
+
+    /// Concatenate the scripts one after another, with `gap` milliseconds between each two of them (see [append](#method.append)).
+    ///
+    /// The result is named and created as the first script (an empty script if there is none).
+    pub fn concat(scripts: &[Script], gap: i64) -> Script {
+        let mut iter = scripts.iter();
+        let mut result = match iter.next() {
+            Some(first) => {
+                let mut result = first.clone();
+                result.cursor = 0;
+                result
+            }
+            None => return Script::empty(),
+        };
+
+        for script in iter {
+            result.append(script, gap);
+        }
+        result
+    }
+
+    /// Merge the other script into this one, interleaving their actions on a shared timeline,
+    /// where the other one starts `offset` milliseconds after this one (negative is taken as 0).
+    ///
+    /// The actions at the same point keep their order, those of this script first.
+    /// The duration lasts until the end of the longer one.
+    ///
+    /// e.g. overlay a recording of [ActionSense::Keyboard](enum.ActionSense.html#variant.Keyboard) with one of [ActionSense::Mouse](enum.ActionSense.html#variant.Mouse)
+    pub fn merge(&mut self, other: &Script, offset: i64) {
+        let offset = offset.max(0);
+        let mut merged = Vec::with_capacity(self.actions.len() + other.actions.len());
+        let mut mine = std::mem::take(&mut self.actions).into_iter().peekable();
+        let mut theirs = other.actions.iter().map(|v| place(self, v, offset)).peekable();
+
+        loop {
+            let next = match (mine.peek(), theirs.peek()) {
+                (Some(a), Some(b)) if a.timeline <= b.timeline => mine.next(),
+                (Some(_), Some(_)) => theirs.next(),
+                (Some(_), None) => mine.next(),
+                (None, _) => theirs.next(),
+            };
+            match next {
+                Some(action) => merged.push(action),
+                None => break,
+            }
+        }
+
+        self.actions = merged;
+        self.duration = self.duration.max(offset + other.duration);
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use crate::canonicalize::declaration::CanonicalAction;
+    use crate::canonicalize::unit_test::{KEYBOARD_SCRIPT_RAW, MOUSE_SCRIPT_RAW, timelines};
+
+    #[test]
+    fn compose_concat() {
+        let keyboard = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        let mouse = Script::load(MOUSE_SCRIPT_RAW).unwrap();
+
+        let script = Script::concat(&[keyboard.clone(), mouse, keyboard.clone()], 100);
+        assert_eq!(script.name, keyboard.name);
+        assert_eq!(timelines(&script), vec![10, 20, 30, 40, 160, 180, 190, 205, 320, 330, 340, 350]);
+        assert_eq!(script.duration, 370);
+
+        // the appended actions are restamped by the first script
+        assert!(script.actions.iter().all(|v| v.ctime == keyboard.ctime + v.timeline));
+        assert!(matches!(script.actions[4].action, CanonicalAction::Mouse(..)));
+
+        assert!(Script::concat(&[], 100).actions.is_empty());
+    }
+
+    #[test]
+    fn compose_merge() {
+        let mut keyboard = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        let mouse = Script::load(MOUSE_SCRIPT_RAW).unwrap();
+
+        keyboard.merge(&mouse, 20);
+        assert_eq!(timelines(&keyboard), vec![10, 20, 20, 30, 40, 40, 50, 65]);
+        assert!(keyboard.actions.iter().all(|v| v.ctime == keyboard.ctime + v.timeline));
+        assert_eq!(keyboard.duration, 70);
+
+        // the actions at the same point keep their order, those of this script first
+        assert!(matches!(keyboard.actions[1].action, CanonicalAction::Keyboard(..)));
+        assert!(matches!(keyboard.actions[2].action, CanonicalAction::Mouse(..)));
+        assert!(matches!(keyboard.actions[4].action, CanonicalAction::Keyboard(..)));
+        assert!(matches!(keyboard.actions[5].action, CanonicalAction::Mouse(..)));
+    }
+}
@@ -1,5 +1,6 @@
 use device_query::{MouseButton, Keycode};
 use crate::canonicalize::declaration::{CanonicalButton, CanonicalKey};
+use crate::error::TapeError;
 
 // TODO: pr for missing keys in device_query
 impl From<Keycode> for CanonicalKey {
@@ -132,7 +133,7 @@ impl PartialEq<CanonicalKey> for Keycode {
 }
 
 impl TryFrom<CanonicalKey> for Keycode {
-    type Error = &'static str;
+    type Error = TapeError;
 
     fn try_from(value: CanonicalKey) -> Result<Self, Self::Error> {
         match value {
@@ -245,7 +246,7 @@ impl TryFrom<CanonicalKey> for Keycode {
             CanonicalKey::Period => Ok(Keycode::Dot),
             CanonicalKey::Slash => Ok(Keycode::Slash),
             CanonicalKey::Space => Ok(Keycode::Space),
-            _ => Err("Unknown key"),
+            _ => Err(TapeError::UnmappableKey(value)),
         }
     }
 }
@@ -264,7 +265,7 @@ impl From<MouseButton> for CanonicalButton {
 }
 
 impl TryFrom<CanonicalButton> for MouseButton {
-    type Error = &'static str;
+    type Error = TapeError;
 
     fn try_from(value: CanonicalButton) -> Result<Self, Self::Error> {
         match value {
@@ -273,7 +274,7 @@ impl TryFrom<CanonicalButton> for MouseButton {
             CanonicalButton::Middle => Ok(3),
             CanonicalButton::Back => Ok(4),
             CanonicalButton::Forward => Ok(5),
-            _ => Err("Unknown button"),
+            _ => Err(TapeError::UnmappableButton(value)),
         }
     }
 }
\ No newline at end of file
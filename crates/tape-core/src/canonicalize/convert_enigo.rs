@@ -1,5 +1,6 @@
 use enigo::{Key, MouseButton};
 use crate::canonicalize::declaration::{CanonicalButton, CanonicalKey};
+use crate::error::TapeError;
 
 impl From<Key> for CanonicalKey {
     fn from(value: Key) -> Self {
@@ -125,7 +126,7 @@ impl From<Key> for CanonicalKey {
 }
 
 impl TryFrom<CanonicalKey> for Key {
-    type Error = &'static str;
+    type Error = TapeError;
 
     fn try_from(value: CanonicalKey) -> Result<Self, Self::Error> {
         match value {
@@ -239,7 +240,7 @@ impl TryFrom<CanonicalKey> for Key {
             CanonicalKey::Slash => Ok(Key::OEM2),
             CanonicalKey::Space => Ok(Key::Space),
             // Unknown keys -- 1
-            CanonicalKey::Unknown => Err("Unknown key"),
+            CanonicalKey::Unknown => Err(TapeError::UnmappableKey(value)),
         }
     }
 }
@@ -258,7 +259,7 @@ impl From<MouseButton> for CanonicalButton {
 }
 
 impl TryFrom<CanonicalButton> for MouseButton {
-    type Error = &'static str;
+    type Error = TapeError;
 
     fn try_from(value: CanonicalButton) -> Result<Self, Self::Error> {
         match value {
@@ -267,7 +268,7 @@ impl TryFrom<CanonicalButton> for MouseButton {
             CanonicalButton::Right => Ok(MouseButton::Right),
             CanonicalButton::Back => Ok(MouseButton::Back),
             CanonicalButton::Forward => Ok(MouseButton::Forward),
-            CanonicalButton::Unknown => Err("Unknown button"),
+            CanonicalButton::Unknown => Err(TapeError::UnmappableButton(value)),
         }
     }
 }
\ No newline at end of file
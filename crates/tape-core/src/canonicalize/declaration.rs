@@ -1,4 +1,6 @@
+use std::str::FromStr;
 use serde::{Deserialize, Serialize};
+use crate::error::TapeError;
 
 // ========== ========== Keyboard/Mouse definitions
 
@@ -188,16 +190,16 @@ impl From<String> for CanonicalKey {
             // Modifier keys -- 13
             "tab" => CanonicalKey::Tab,
             "capslock" => CanonicalKey::CapsLock,
-            "lshift" => CanonicalKey::LShift,
+            "lshift" | "shift" => CanonicalKey::LShift,
             "rshift" => CanonicalKey::RShift,
-            "lctrl" => CanonicalKey::LCtrl,
+            "lctrl" | "ctrl" | "control" => CanonicalKey::LCtrl,
             "rctrl" => CanonicalKey::RCtrl,
             // note: 'alt' on windows and linux, 'option' on mac
-            "lalt" => CanonicalKey::LAlt,
+            "lalt" | "alt" | "option" => CanonicalKey::LAlt,
             // note: 'alt' on windows and linux, 'option' on mac
             "ralt" => CanonicalKey::RAlt,
             // note: 'command' on mac, 'windows' on windows, 'super' on linux
-            "lmeta" => CanonicalKey::LMeta,
+            "lmeta" | "meta" | "cmd" | "command" | "win" | "super" => CanonicalKey::LMeta,
             // note: 'command' on mac, 'windows' on windows, 'super' on linux
             "rmeta" => CanonicalKey::RMeta,
             // note: 'menu' on windows
@@ -259,6 +261,23 @@ impl From<String> for CanonicalKey {
     }
 }
 
+impl CanonicalKey {
+    /// Get the key on the other side of the keyboard (e.g. [RCtrl](#variant.RCtrl) for [LCtrl](#variant.LCtrl)), or the key itself if there is none
+    pub fn counterpart(&self) -> CanonicalKey {
+        match *self {
+            CanonicalKey::LShift => CanonicalKey::RShift,
+            CanonicalKey::RShift => CanonicalKey::LShift,
+            CanonicalKey::LCtrl => CanonicalKey::RCtrl,
+            CanonicalKey::RCtrl => CanonicalKey::LCtrl,
+            CanonicalKey::LAlt => CanonicalKey::RAlt,
+            CanonicalKey::RAlt => CanonicalKey::LAlt,
+            CanonicalKey::LMeta => CanonicalKey::RMeta,
+            CanonicalKey::RMeta => CanonicalKey::LMeta,
+            other => other,
+        }
+    }
+}
+
 impl From<String> for CanonicalButton {
     fn from(value: String) -> Self {
         match value.to_lowercase().as_str() {
@@ -397,6 +416,79 @@ impl ToString for CanonicalButton {
     }
 }
 
+// ========== ========== Chord definitions
+
+/// A key chord (hotkey), e.g. "ctrl+shift+t" -- the modifiers held (in order) while the key is pressed
+#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq)]
+pub struct CanonicalChord {
+    /// The keys held down before the key, and released (in reverse order) after it
+    pub modifiers: Vec<CanonicalKey>,
+    /// The key pressed and released
+    pub key: CanonicalKey,
+}
+
+impl CanonicalChord {
+    /// Create a chord
+    pub fn new(modifiers: Vec<CanonicalKey>, key: CanonicalKey) -> CanonicalChord {
+        CanonicalChord { modifiers, key }
+    }
+
+    /// Whether the key is one of the modifiers (either side of the keyboard counts)
+    pub fn has_modifier(&self, key: CanonicalKey) -> bool {
+        self.modifiers.iter().any(|m| *m == key || m.counterpart() == key)
+    }
+
+    /// Expand the chord into the key by key actions: the modifiers and the key are pressed in order, then released in reverse order
+    pub fn actions(&self) -> Vec<CanonicalAction> {
+        let keys: Vec<CanonicalKey> = self.modifiers.iter().copied().chain(std::iter::once(self.key)).collect();
+        keys.iter().map(|key| CanonicalAction::Keyboard(ActionType::Press, *key))
+            .chain(keys.iter().rev().map(|key| CanonicalAction::Keyboard(ActionType::Release, *key)))
+            .collect()
+    }
+
+    /// Whether pressing (or releasing) the key triggers the chord, given the keys held down
+    pub fn is_triggered(&self, key: CanonicalKey, held: &[CanonicalKey]) -> bool {
+        key == self.key && self.modifiers.iter().all(|m| held.iter().any(|h| h == m || *h == m.counterpart()))
+    }
+}
+
+impl From<CanonicalKey> for CanonicalChord {
+    fn from(key: CanonicalKey) -> Self {
+        CanonicalChord::new(vec![], key)
+    }
+}
+
+/// Parse a chord like "ctrl+shift+t", the last part is the key and the others are the modifiers
+/// (every part is parsed as a [CanonicalKey](enum.CanonicalKey.html), so a single key is a chord as well).
+///
+/// Fail with [TapeError::InvalidChord](../../error/enum.TapeError.html#variant.InvalidChord) if a part is empty or not a known key.
+impl FromStr for CanonicalChord {
+    type Err = TapeError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut keys = Vec::new();
+        for part in value.split('+').map(|part| part.trim()) {
+            match CanonicalKey::from(part.to_string()) {
+                CanonicalKey::Unknown => return Err(TapeError::InvalidChord(value.to_string())),
+                key => keys.push(key),
+            }
+        }
+
+        let key = keys.pop().ok_or_else(|| TapeError::InvalidChord(value.to_string()))?;
+        Ok(CanonicalChord::new(keys, key))
+    }
+}
+
+impl ToString for CanonicalChord {
+    fn to_string(&self) -> String {
+        self.modifiers.iter()
+            .chain(std::iter::once(&self.key))
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+}
+
 // ========== ========== Action definitions
 
 /// The type of an action
@@ -409,7 +501,7 @@ pub enum ActionType {
 }
 
 /// The information of an action
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum CanonicalAction {
     /// A keyboard action -- Tuple(type, key)
     /// - type: press/release
@@ -420,4 +512,33 @@ pub enum CanonicalAction {
     /// - button: the button to press/release/move
     /// - pos: the position to move to or the position of the mouse
     Mouse(ActionType, CanonicalButton, (i32, i32)),
+    /// A key chord -- Tuple(chord), the modifiers are pressed in order, then the key is pressed and released,
+    /// then the modifiers are released in reverse order
+    Chord(CanonicalChord),
+    /// A mouse wheel action -- Tuple(delta)
+    /// - delta: the steps scrolled horizontally and vertically (positive for right/down)
+    Scroll((i32, i32)),
+    /// A text to type -- Struct{text, delay}
+    /// - text: the characters to type (any unicode, regardless of the keyboard layout)
+    /// - delay: the delay between two characters in milliseconds, all the characters are typed at once if not set
+    Text {
+        text: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        delay: Option<i64>,
+    },
+    /// A marker on the timeline -- Tuple(label), it is kept for reference and never performed
+    /// - label: what happens at the point (e.g. "pause"/"resume" of a recording, or "login done")
+    Marker(String),
+    /// A free-text comment -- Tuple(text), it is kept for reference and never performed
+    Comment(String),
+    /// An explicit wait -- Tuple(ms), the actor holds at the point for the milliseconds before going on
+    /// (the wait is not on the timeline, so the following actions are delayed by it)
+    Wait(i64),
+}
+
+impl CanonicalAction {
+    /// Whether the action is a pseudo-action (marker, comment or wait), which dispatches no input
+    pub fn is_pseudo(&self) -> bool {
+        matches!(self, CanonicalAction::Marker(_) | CanonicalAction::Comment(_) | CanonicalAction::Wait(_))
+    }
 }
\ No newline at end of file
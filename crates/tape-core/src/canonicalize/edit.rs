@@ -0,0 +1,260 @@
+use std::ops::Range;
+use crate::canonicalize::{Action, Script};
+use crate::error::TapeError;
+
+/// What to do with the time left by the deleted actions
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Gap {
+    /// Leave the later actions where they are
+    Keep,
+    /// Bring the later actions forward to fill the time
+    Close,
+}
+
+/// Move the actions in time (along with their timestamps)
+pub(crate) fn shift_all(actions: &mut [Action], offset: i64) {
+    for action in actions.iter_mut() {
+        action.timeline += offset;
+        action.ctime += offset;
+    }
+}
+
+// Collection of methods of Script on 'edit'
+impl Script {
+    /// Check the index range, fail if it is reversed or out of the actions
+    fn check_range(&self, range: &Range<usize>) -> Result<(), TapeError> {
+        match range.start <= range.end && range.end <= self.actions.len() {
+            true => Ok(()),
+            false => Err(TapeError::InvalidRange { start: range.start as i64, end: range.end as i64 }),
+        }
+    }
+
+    /// Get the time the actions in the index range take up,
+    /// from the first one till the one after the range (or the end of the script)
+    fn span_of(&self, range: &Range<usize>) -> (i64, i64) {
+        let end = self.actions.get(range.end).map_or(self.duration, |v| v.timeline);
+        let start = self.actions.get(range.start).map_or(end, |v| v.timeline);
+        (start, end)
+    }
+
+    /// Take the edited actions and duration if the self-check passes,
+    /// otherwise keep the script untouched and return the error
+    fn commit_edit(&mut self, actions: Vec<Action>, duration: i64) -> Result<(), TapeError> {
+        let prev_actions = std::mem::replace(&mut self.actions, actions);
+        let prev_duration = std::mem::replace(&mut self.duration, duration);
+
+        match self.self_check() {
+            Ok(_) => Ok(()),
+            Err(check_err) => {
+                self.actions = prev_actions;
+                self.duration = prev_duration;
+                Err(check_err)
+            }
+        }
+    }
+
+    /// Insert the actions at the point of the timeline (after the actions already at the point).
+    ///
+    /// The timelines of the inserted actions are relative to the point, and the later actions
+    /// (as well as the duration) are put off by the timeline of the last inserted one.
+    /// ---
+    /// return:
+    /// - **Ok(Range)**: The indexes of the inserted actions
+    /// - **Err(TapeError::InvalidRange)**: The point is out of the script
+    /// - **Err(TapeError::SelfCheck)**: The inserted actions are not sorted by their timeline (or before the point)
+    pub fn insert(&mut self, at: i64, inserted: Vec<Action>) -> Result<Range<usize>, TapeError> {
+        if at < 0 || at > self.duration {
+            return Err(TapeError::InvalidRange { start: at, end: at });
+        }
+
+        let index = self.actions.partition_point(|v| v.timeline <= at);
+        self.insert_at(index, at, inserted)
+    }
+
+    /// Insert the actions before the index, at the point of the timeline (see [insert](#method.insert))
+    fn insert_at(&mut self, index: usize, at: i64, mut inserted: Vec<Action>) -> Result<Range<usize>, TapeError> {
+        if let Some(offset) = inserted.iter().position(|v| v.timeline < 0) {
+            return Err(TapeError::SelfCheck { index: index + offset, reason: "The action is before the point of insertion!".to_string() });
+        }
+
+        // 1 - put the inserted actions at the point
+        let span = inserted.last().map_or(0, |v| v.timeline);
+        for action in inserted.iter_mut() {
+            action.timeline += at;
+            action.ctime = self.ctime + action.timeline;
+        }
+
+        // 2 - put off the later actions
+        let count = inserted.len();
+        let mut actions = self.actions.clone();
+        shift_all(&mut actions[index..], span);
+        actions.splice(index..index, inserted);
+
+        self.commit_edit(actions, self.duration + span)?;
+        Ok(index..index + count)
+    }
+
+    /// Delete the actions in the index range, return the deleted ones.
+    ///
+    /// With [Gap::Close](edit/enum.Gap.html#variant.Close), the time from the first deleted action till the one after the range is cut out.
+    /// ---
+    /// return:
+    /// - **Ok(Vec)**: The deleted actions
+    /// - **Err(TapeError::InvalidRange)**: The range is reversed or out of the actions
+    pub fn delete(&mut self, range: Range<usize>, gap: Gap) -> Result<Vec<Action>, TapeError> {
+        self.check_range(&range)?;
+
+        let (start, end) = self.span_of(&range);
+        let cut = match gap {
+            Gap::Keep => 0,
+            Gap::Close => end - start,
+        };
+
+        let mut actions = self.actions.clone();
+        let deleted: Vec<Action> = actions.drain(range.clone()).collect();
+        shift_all(&mut actions[range.start..], -cut);
+
+        self.commit_edit(actions, self.duration - cut)?;
+        Ok(deleted)
+    }
+
+    /// Delete the actions within the time range (from `from` inclusive till `to` exclusive), return the deleted ones.
+    ///
+    /// With [Gap::Close](edit/enum.Gap.html#variant.Close), the time range is cut out (as far as the end of the script).
+    /// ---
+    /// return:
+    /// - **Ok(Vec)**: The deleted actions
+    /// - **Err(TapeError::InvalidRange)**: The range is reversed or starts out of the script
+    pub fn delete_between(&mut self, from: i64, to: i64, gap: Gap) -> Result<Vec<Action>, TapeError> {
+        if from < 0 || from > to || from > self.duration {
+            return Err(TapeError::InvalidRange { start: from, end: to });
+        }
+
+        let start = self.actions.partition_point(|v| v.timeline < from);
+        let end = self.actions.partition_point(|v| v.timeline < to);
+        let cut = match gap {
+            Gap::Keep => 0,
+            Gap::Close => to.min(self.duration) - from,
+        };
+
+        let mut actions = self.actions.clone();
+        let deleted: Vec<Action> = actions.drain(start..end).collect();
+        shift_all(&mut actions[start..], -cut);
+
+        self.commit_edit(actions, self.duration - cut)?;
+        Ok(deleted)
+    }
+
+    /// Move the actions in the index range in time by the offset (in milliseconds, negative to bring them forward).
+    ///
+    /// The duration is extended if the moved actions go beyond the end.
+    /// ---
+    /// return:
+    /// - **Ok(())**: The actions are moved
+    /// - **Err(TapeError::InvalidRange)**: The range is reversed or out of the actions
+    /// - **Err(TapeError::SelfCheck)**: The moved actions would pass their neighbours (or go before the beginning)
+    pub fn shift(&mut self, range: Range<usize>, offset: i64) -> Result<(), TapeError> {
+        self.check_range(&range)?;
+
+        let mut actions = self.actions.clone();
+        shift_all(&mut actions[range], offset);
+        let duration = actions.last().map_or(self.duration, |v| v.timeline.max(self.duration));
+
+        self.commit_edit(actions, duration)
+    }
+
+    /// Replace the actions in the index range with the given ones, return the replaced ones.
+    ///
+    /// It is the same as [delete](#method.delete) (closing the gap) and then [insert](#method.insert) where the first replaced action was,
+    /// but the script is left untouched if either fails.
+    /// ---
+    /// return:
+    /// - **Ok(Vec)**: The replaced actions
+    /// - **Err(TapeError::InvalidRange)**: The range is reversed or out of the actions
+    /// - **Err(TapeError::SelfCheck)**: The given actions are not sorted by their timeline
+    pub fn replace(&mut self, range: Range<usize>, actions: Vec<Action>) -> Result<Vec<Action>, TapeError> {
+        self.check_range(&range)?;
+
+        let (start, _) = self.span_of(&range);
+        let (prev_actions, prev_duration) = (self.actions.clone(), self.duration);
+        let index = range.start;
+        let replaced = self.delete(range, Gap::Close)?;
+        match self.insert_at(index, start, actions) {
+            Ok(_) => Ok(replaced),
+            Err(err) => {
+                self.actions = prev_actions;
+                self.duration = prev_duration;
+                Err(err)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use crate::canonicalize::unit_test::{KEYBOARD_SCRIPT_RAW, timelines};
+
+    fn marker(timeline: i64) -> Action {
+        let mut action = Action::from_marker("x");
+        action.timeline = timeline;
+        action
+    }
+
+    #[test]
+    fn edit_insert_delete() {
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+
+        // the later actions are put off by the inserted ones
+        assert_eq!(script.insert(20, vec![marker(0), marker(5)]).unwrap(), 2..4);
+        assert_eq!(timelines(&script), vec![10, 20, 20, 25, 35, 45]);
+        assert_eq!(script.duration, 65);
+        assert_eq!(script.actions[3].ctime, script.ctime + 25);
+
+        // the gap is kept or closed
+        assert_eq!(script.delete(2..4, Gap::Keep).unwrap().len(), 2);
+        assert_eq!(timelines(&script), vec![10, 20, 35, 45]);
+        script.delete(1..2, Gap::Close).unwrap();
+        assert_eq!(timelines(&script), vec![10, 20, 30]);
+        assert_eq!(script.duration, 50);
+
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        assert_eq!(script.delete_between(15, 35, Gap::Close).unwrap().len(), 2);
+        assert_eq!(timelines(&script), vec![10, 20]);
+        assert_eq!(script.duration, 40);
+        assert!(script.delete_between(15, 100, Gap::Close).is_ok());
+        assert_eq!(script.duration, 15);
+
+        // the invalid edits leave the script untouched
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        assert_eq!(script.insert(70, vec![]).unwrap_err(), TapeError::InvalidRange { start: 70, end: 70 });
+        assert!(matches!(script.insert(20, vec![marker(5), marker(0)]).unwrap_err(), TapeError::SelfCheck { index: 3, .. }));
+        assert_eq!(script.delete(3..5, Gap::Keep).unwrap_err(), TapeError::InvalidRange { start: 3, end: 5 });
+        assert_eq!(timelines(&script), vec![10, 20, 30, 40]);
+        assert_eq!(script.duration, 60);
+    }
+
+    #[test]
+    fn edit_shift_replace() {
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        script.shift(1..3, 5).unwrap();
+        assert_eq!(timelines(&script), vec![10, 25, 35, 40]);
+
+        // the moved actions may not pass their neighbours
+        assert!(matches!(script.shift(1..2, -20).unwrap_err(), TapeError::SelfCheck { index: 1, .. }));
+        assert_eq!(timelines(&script), vec![10, 25, 35, 40]);
+
+        // the duration is extended
+        script.shift(3..4, 30).unwrap();
+        assert_eq!(script.duration, 70);
+
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        let replaced = script.replace(1..3, vec![marker(0), marker(3), marker(6)]).unwrap();
+        assert_eq!(replaced.iter().map(|v| v.timeline).collect::<Vec<_>>(), vec![20, 30]);
+        assert_eq!(timelines(&script), vec![10, 20, 23, 26, 26]);
+        assert_eq!(script.duration, 46);
+
+        assert!(script.replace(0..1, vec![marker(3), marker(0)]).is_err());
+        assert_eq!(timelines(&script), vec![10, 20, 23, 26, 26]);
+    }
+}
@@ -0,0 +1,261 @@
+use std::str::FromStr;
+use serde::{Deserialize, Serialize};
+use crate::canonicalize::migrate::{self, FORMAT_VERSION};
+use crate::canonicalize::Script;
+use crate::error::TapeError;
+
+/// The leading bytes of a script in [Format::Binary](enum.Format.html#variant.Binary)
+pub const BINARY_MAGIC: &[u8] = b"TAPE\0";
+
+/// The serialization of a script
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Format {
+    /// Human-readable and editable, but slow and verbose for long scripts
+    Toml,
+    /// Human-readable and much faster to load than TOML
+    Json,
+    /// [BINARY_MAGIC](constant.BINARY_MAGIC.html) followed by the script in MessagePack
+    /// (the fields of the actions are positional, the ones with the names are loaded as well)
+    Binary,
+}
+
+impl Format {
+    /// Detect the format of the raw bytes (TOML unless it looks like the others)
+    pub fn detect(raw: &[u8]) -> Format {
+        if raw.starts_with(BINARY_MAGIC) {
+            return Format::Binary;
+        }
+        match raw.iter().find(|v| !v.is_ascii_whitespace()) {
+            // a TOML document never starts with a brace
+            Some(b'{') => Format::Json,
+            _ => Format::Toml,
+        }
+    }
+}
+
+/// Parse the name of a format (case-insensitive), fail with [TapeError::UnknownFormat](../../error/enum.TapeError.html#variant.UnknownFormat) if it is none of them.
+impl FromStr for Format {
+    type Err = TapeError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.to_lowercase().as_str() {
+            "toml" => Ok(Format::Toml),
+            "json" => Ok(Format::Json),
+            "binary" => Ok(Format::Binary),
+            _ => Err(TapeError::UnknownFormat(value.to_string())),
+        }
+    }
+}
+
+/// The header of a script, all the other fields are ignored
+#[derive(Deserialize)]
+struct Header {
+    #[serde(default = "first_version")]
+    format_version: u32,
+}
+
+fn first_version() -> u32 { 1 }
+
+/// A script headed by the current format version, to be published
+#[derive(Serialize)]
+struct Document<'a> {
+    format_version: u32,
+    #[serde(flatten)]
+    script: &'a Script,
+}
+
+impl<'a> Document<'a> {
+    fn new(script: &'a Script) -> Document<'a> {
+        Document { format_version: FORMAT_VERSION, script }
+    }
+}
+
+/// Deserialize the script in the current version directly,
+/// otherwise (in an older version) through the [migrations](../migrate/index.html).
+fn decode<E>(
+    header: Result<Header, E>,
+    as_script: impl FnOnce() -> Result<Script, E>,
+    as_table: impl FnOnce() -> Result<toml::Table, E>,
+    to_error: impl Fn(E) -> TapeError,
+) -> Result<Script, TapeError> {
+    let header = header.map_err(&to_error)?;
+    if migrate::check_version(header.format_version)? {
+        let mut doc = as_table().map_err(&to_error)?;
+        migrate::migrate(&mut doc)?;
+        doc.try_into::<Script>().map_err(|e| TapeError::Parse { message: e.message().to_string(), line: None, column: None })
+    } else {
+        as_script().map_err(&to_error)
+    }
+}
+
+fn from_json(err: serde_json::Error) -> TapeError {
+    // the line of an error not about the syntax is 0
+    let (line, column) = match err.line() {
+        0 => (None, None),
+        line => (Some(line), Some(err.column())),
+    };
+    TapeError::Parse { message: err.to_string(), line, column }
+}
+
+fn from_binary(err: rmp_serde::decode::Error) -> TapeError {
+    TapeError::Parse { message: err.to_string(), line: None, column: None }
+}
+
+impl Script {
+    /// Load a script from the raw bytes of the format, see [load](#method.load).
+    pub fn load_as(raw: &[u8], format: Format) -> Result<Script, TapeError> {
+        let script = match format {
+            Format::Toml => {
+                let raw = std::str::from_utf8(raw)
+                    .map_err(|e| TapeError::Parse { message: e.to_string(), line: None, column: None })?;
+                return Script::load(raw);
+            }
+            Format::Json => decode(
+                serde_json::from_slice::<Header>(raw),
+                || serde_json::from_slice::<Script>(raw),
+                || serde_json::from_slice::<toml::Table>(raw),
+                from_json,
+            )?,
+            Format::Binary => {
+                let body = raw.strip_prefix(BINARY_MAGIC)
+                    .ok_or(TapeError::Parse { message: "the binary magic is missing".to_string(), line: None, column: None })?;
+                decode(
+                    rmp_serde::from_slice::<Header>(body),
+                    || rmp_serde::from_slice::<Script>(body),
+                    || rmp_serde::from_slice::<toml::Table>(body),
+                    from_binary,
+                )?
+            }
+        };
+
+        script.self_check()?;
+        Ok(script)
+    }
+
+    /// Load a script from the raw bytes, the format is [detected](format/enum.Format.html#method.detect).
+    pub fn load_bytes(raw: &[u8]) -> Result<Script, TapeError> {
+        Script::load_as(raw, Format::detect(raw))
+    }
+
+    /// Publish the script in the format, headed by the current format version
+    pub fn publish_as(&self, format: Format) -> Result<Vec<u8>, TapeError> {
+        self.self_check()?;
+
+        let document = Document::new(self);
+        match format {
+            Format::Toml => self.publish().map(String::into_bytes),
+            Format::Json => serde_json::to_vec(&document).map_err(|e| TapeError::Serialize(e.to_string())),
+            Format::Binary => {
+                let mut raw = BINARY_MAGIC.to_vec();
+                raw.extend(rmp_serde::to_vec(&document).map_err(|e| TapeError::Serialize(e.to_string()))?);
+                Ok(raw)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use crate::canonicalize::declaration::CanonicalAction;
+
+    // region mock script raw
+    const MOCK_SCRIPT_RAW: &str = r##"
+name = "mock"
+ctime = 1695739924720
+duration = 40
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action]
+Keyboard = ["Press", "KeyA"]
+
+[[actions]]
+ctime = 1695739924740
+timeline = 20
+
+[actions.action]
+Mouse = ["Move", "Unknown", [-3, 4]]
+
+[[actions]]
+ctime = 1695739924750
+timeline = 30
+
+[actions.action.Text]
+text = "hi"
+delay = 5
+
+[[actions]]
+ctime = 1695739924760
+timeline = 40
+
+[actions.action]
+Keyboard = ["Release", "KeyA"]
+        "##;
+    // endregion
+
+    fn actions_of(script: &Script) -> Vec<(i64, i64, CanonicalAction)> {
+        script.actions.iter().map(|a| (a.ctime, a.timeline, a.action.clone())).collect()
+    }
+
+    #[test]
+    fn format_round_trip() {
+        let script = Script::load(MOCK_SCRIPT_RAW).unwrap();
+        assert_eq!(script.actions.len(), 4);
+        for format in [Format::Toml, Format::Json, Format::Binary] {
+            let raw = script.publish_as(format).unwrap();
+            assert_eq!(Format::detect(&raw), format);
+
+            let loaded = Script::load_bytes(&raw).unwrap();
+            assert_eq!(loaded.name, script.name);
+            assert_eq!(loaded.duration, script.duration);
+            assert_eq!(actions_of(&loaded), actions_of(&script));
+        }
+
+        // the fields of the actions are positional in the binary, the ones with the names are loaded as well
+        let positional = script.publish_as(Format::Binary).unwrap();
+        assert!(!positional.windows(8).any(|v| v == b"timeline"));
+        let mut named = BINARY_MAGIC.to_vec();
+        rmp_serde::encode::write_named(&mut named, &Document::new(&script)).unwrap();
+        assert!(named.len() > positional.len());
+        assert_eq!(actions_of(&Script::load_bytes(&named).unwrap()), actions_of(&script));
+
+        // the JSON text is loaded as well
+        let raw = String::from_utf8(script.publish_as(Format::Json).unwrap()).unwrap();
+        assert!(raw.starts_with(&format!("{{\"format_version\":{}", FORMAT_VERSION)));
+        assert_eq!(actions_of(&Script::load(&raw).unwrap()), actions_of(&script));
+    }
+
+    #[test]
+    fn format_parse() {
+        assert_eq!("toml".parse::<Format>(), Ok(Format::Toml));
+        assert_eq!("JSON".parse::<Format>(), Ok(Format::Json));
+        assert_eq!("Binary".parse::<Format>(), Ok(Format::Binary));
+        assert_eq!("yaml".parse::<Format>(), Err(TapeError::UnknownFormat("yaml".to_string())));
+    }
+
+    #[test]
+    fn format_load_error() {
+        // the versions are handled the same way
+        let raw = format!("{{\"format_version\":{},\"name\":\"x\",\"ctime\":0,\"duration\":0,\"actions\":[]}}", FORMAT_VERSION + 1);
+        assert!(matches!(Script::load(&raw).unwrap_err(), TapeError::UnsupportedVersion { .. }));
+
+        // and so is the self-check
+        let raw = "{\"name\":\"x\",\"ctime\":0,\"duration\":-1,\"actions\":[]}";
+        assert!(matches!(Script::load_bytes(raw.as_bytes()).unwrap_err(), TapeError::SelfCheck { .. }));
+
+        // the syntax error is located
+        let raw = "{\n\"name\": \"x\",\n\"ctime\": oops }";
+        match Script::load(raw).unwrap_err() {
+            TapeError::Parse { line, .. } => assert_eq!(line, Some(3)),
+            other => panic!("unexpected error: {}", other),
+        }
+
+        // the binary is detected by the magic
+        let mut raw = BINARY_MAGIC.to_vec();
+        raw.extend_from_slice(b"garbage");
+        assert!(matches!(Script::load_bytes(&raw).unwrap_err(), TapeError::Parse { .. }));
+    }
+}
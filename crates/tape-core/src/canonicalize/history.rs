@@ -0,0 +1,315 @@
+use std::ops::Range;
+use serde::{Deserialize, Serialize};
+use crate::canonicalize::{Action, Script};
+use crate::canonicalize::edit::{Gap, shift_all};
+use crate::error::TapeError;
+
+/// The difference an edit makes to a script, which can be applied and reverted
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Patch {
+    /// The index of the first changed action
+    index: usize,
+    /// The actions taken out (from the index)
+    removed: Vec<Action>,
+    /// The actions put in their place
+    added: Vec<Action>,
+    /// How far the actions after them are moved in time
+    shift: i64,
+    /// The duration before and after the edit
+    duration: (i64, i64),
+}
+
+impl Patch {
+    /// Find the difference between the actions before and after the edit
+    fn between(before: &[Action], after: &[Action], duration: (i64, i64)) -> Patch {
+        // the actions untouched at the beginning
+        let prefix = before.iter().zip(after).take_while(|(a, b)| a == b).count();
+
+        // the actions just moved in time at the end
+        let shift = match (before.last(), after.last()) {
+            (Some(a), Some(b)) => b.timeline - a.timeline,
+            _ => 0,
+        };
+        let suffix = before.iter().rev().zip(after.iter().rev())
+            .take(before.len().min(after.len()) - prefix)
+            .take_while(|(a, b)| a.timeline + shift == b.timeline && a.ctime + shift == b.ctime && a.action == b.action)
+            .count();
+
+        Patch {
+            index: prefix,
+            removed: before[prefix..before.len() - suffix].to_vec(),
+            added: after[prefix..after.len() - suffix].to_vec(),
+            shift: if suffix == 0 { 0 } else { shift },
+            duration,
+        }
+    }
+
+    /// Whether the edit changes nothing
+    fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.shift == 0 && self.duration.0 == self.duration.1
+    }
+
+    fn apply(&self, script: &mut Script) {
+        let end = self.index + self.removed.len();
+        script.actions.splice(self.index..end, self.added.iter().cloned());
+        shift_all(&mut script.actions[self.index + self.added.len()..], self.shift);
+        script.duration = self.duration.1;
+    }
+
+    fn revert(&self, script: &mut Script) {
+        let end = self.index + self.added.len();
+        script.actions.splice(self.index..end, self.removed.iter().cloned());
+        shift_all(&mut script.actions[self.index + self.removed.len()..], -self.shift);
+        script.duration = self.duration.0;
+    }
+}
+
+/// A step of the history, which is undone/redone at once
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct Step {
+    /// What the step is, e.g. "shift"
+    label: String,
+    /// The edits in the step, in the order they are made
+    patches: Vec<Patch>,
+    /// The time (of the script's clock) of the last edit in the step
+    at: i64,
+}
+
+/// An **edit session** is a [script](../struct.Script.html) being edited, with the history of the edits for undo/redo.
+///
+/// Every edit is recorded as a reversible step, and the history is unlimited.
+/// A burst of edits can be coalesced into one step, by a [group](#method.begin_group) or by the [coalescing window](#method.set_coalesce_window).
+///
+/// The session (including the history) can be serialized, so that the history survives reopening the script.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EditSession {
+    script: Script,
+    undo: Vec<Step>,
+    redo: Vec<Step>,
+    /// The edits with the same label within the window (in milliseconds) are coalesced into one step, 0 to disable it
+    #[serde(default)]
+    coalesce_window: i64,
+    /// The label of the open group, if any
+    #[serde(skip)]
+    group: Option<String>,
+    /// Whether the last step is closed to coalescing (e.g. it is the first one of the group, or it has been undone/redone)
+    #[serde(skip)]
+    sealed: bool,
+}
+
+impl EditSession {
+    /// Start editing the script, with an empty history
+    pub fn new(script: Script) -> EditSession {
+        EditSession { script, undo: vec![], redo: vec![], coalesce_window: 0, group: None, sealed: false }
+    }
+
+    /// Get the script being edited
+    pub fn script(&self) -> &Script {
+        &self.script
+    }
+
+    /// Stop editing, return the script
+    pub fn into_script(self) -> Script {
+        self.script
+    }
+
+    /// Set the window (in milliseconds) in which the edits of the same label are coalesced into one step (default to 0, i.e. never)
+    pub fn set_coalesce_window(&mut self, window: i64) {
+        self.coalesce_window = window;
+    }
+
+    /// Make the later edits one step (under the label), until [end_group](#method.end_group) is called
+    pub fn begin_group(&mut self, label: &str) {
+        self.group = Some(label.to_string());
+        self.sealed = true;
+    }
+
+    /// Close the group, the later edits are in their own steps
+    pub fn end_group(&mut self) {
+        if self.group.take().is_some() {
+            self.sealed = true;
+        }
+    }
+
+    /// Make an edit to the script under the label, record it in the history if it succeeds and changes something.
+    ///
+    /// Any edit made in place of the methods of the session (e.g. a mix of them) can be recorded in this way,
+    /// as long as it leaves the script untouched when it fails.
+    pub fn edit<T>(&mut self, label: &str, f: impl FnOnce(&mut Script) -> Result<T, TapeError>) -> Result<T, TapeError> {
+        let before = self.script.actions.clone();
+        let duration = self.script.duration;
+        let result = f(&mut self.script)?;
+
+        let patch = Patch::between(&before, &self.script.actions, (duration, self.script.duration));
+        if !patch.is_empty() {
+            self.record(label, patch);
+        }
+        Ok(result)
+    }
+
+    /// Put the patch into the history, coalescing it into the last step if it is in the same group (or window)
+    fn record(&mut self, label: &str, patch: Patch) {
+        let now = self.script.clock().now();
+        self.redo.clear();
+
+        let label = self.group.as_deref().unwrap_or(label);
+        let window = match self.group {
+            Some(_) => i64::MAX,
+            None if self.coalesce_window > 0 => self.coalesce_window,
+            None => -1,
+        };
+
+        match self.undo.last_mut() {
+            Some(last) if !self.sealed && last.label == label && now - last.at <= window => {
+                last.patches.push(patch);
+                last.at = now;
+            }
+            _ => self.undo.push(Step { label: label.to_string(), patches: vec![patch], at: now }),
+        }
+        self.sealed = false;
+    }
+
+    /// Whether there is a step to undo
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    /// Whether there is a step to redo
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Undo the last step, return its label (None if there is nothing to undo)
+    pub fn undo(&mut self) -> Option<String> {
+        self.end_group();
+        self.sealed = true;
+        let step = self.undo.pop()?;
+        for patch in step.patches.iter().rev() {
+            patch.revert(&mut self.script);
+        }
+        let label = step.label.clone();
+        self.redo.push(step);
+        Some(label)
+    }
+
+    /// Redo the last undone step, return its label (None if there is nothing to redo)
+    pub fn redo(&mut self) -> Option<String> {
+        self.end_group();
+        self.sealed = true;
+        let step = self.redo.pop()?;
+        for patch in step.patches.iter() {
+            patch.apply(&mut self.script);
+        }
+        let label = step.label.clone();
+        self.undo.push(step);
+        Some(label)
+    }
+
+    /// See [Script::insert](../struct.Script.html#method.insert)
+    pub fn insert(&mut self, at: i64, actions: Vec<Action>) -> Result<Range<usize>, TapeError> {
+        self.edit("insert", |script| script.insert(at, actions))
+    }
+
+    /// See [Script::delete](../struct.Script.html#method.delete)
+    pub fn delete(&mut self, range: Range<usize>, gap: Gap) -> Result<Vec<Action>, TapeError> {
+        self.edit("delete", |script| script.delete(range, gap))
+    }
+
+    /// See [Script::delete_between](../struct.Script.html#method.delete_between)
+    pub fn delete_between(&mut self, from: i64, to: i64, gap: Gap) -> Result<Vec<Action>, TapeError> {
+        self.edit("delete", |script| script.delete_between(from, to, gap))
+    }
+
+    /// See [Script::shift](../struct.Script.html#method.shift)
+    pub fn shift(&mut self, range: Range<usize>, offset: i64) -> Result<(), TapeError> {
+        self.edit("shift", |script| script.shift(range, offset))
+    }
+
+    /// See [Script::replace](../struct.Script.html#method.replace)
+    pub fn replace(&mut self, range: Range<usize>, actions: Vec<Action>) -> Result<Vec<Action>, TapeError> {
+        self.edit("replace", |script| script.replace(range, actions))
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use crate::canonicalize::unit_test::{KEYBOARD_SCRIPT_RAW, timelines};
+    use crate::clock::VirtualClock;
+    use std::sync::Arc;
+
+    #[test]
+    fn history_undo_redo() {
+        let clock = VirtualClock::new(0);
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        script.set_clock(Arc::new(clock.clone()));
+        let mut session = EditSession::new(script);
+        let original = timelines(session.script());
+
+        session.insert(20, vec![Action::from_marker("x")]).unwrap();
+        session.delete(0..1, Gap::Close).unwrap();
+        session.shift(2..4, 5).unwrap();
+        let changed = timelines(session.script());
+        assert_eq!(changed, vec![10, 10, 25, 35]);
+        assert_eq!(session.script().duration, 50);
+
+        // a failed edit is not in the history
+        assert!(session.shift(0..1, -20).is_err());
+
+        assert_eq!(session.undo().as_deref(), Some("shift"));
+        assert_eq!(session.undo().as_deref(), Some("delete"));
+        assert_eq!(session.undo().as_deref(), Some("insert"));
+        assert_eq!(session.undo(), None);
+        assert_eq!(timelines(session.script()), original);
+        assert_eq!(session.script().duration, 60);
+        assert_eq!(session.script().actions[1].ctime, session.script().ctime + 20);
+
+        assert_eq!(session.redo().as_deref(), Some("insert"));
+        assert_eq!(session.redo().as_deref(), Some("delete"));
+        assert_eq!(session.redo().as_deref(), Some("shift"));
+        assert_eq!(timelines(session.script()), changed);
+
+        // a new edit clears the redo
+        session.undo();
+        session.shift(3..4, 1).unwrap();
+        assert!(!session.can_redo());
+
+        // the history survives the serialization
+        let raw = serde_json::to_string(&session).unwrap();
+        let mut session: EditSession = serde_json::from_str(&raw).unwrap();
+        while session.undo().is_some() {}
+        assert_eq!(timelines(session.script()), original);
+    }
+
+    #[test]
+    fn history_coalesce() {
+        let clock = VirtualClock::new(0);
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        script.set_clock(Arc::new(clock.clone()));
+        let mut session = EditSession::new(script);
+        let original = timelines(session.script());
+
+        // a burst of the same edit within the window is one step
+        session.set_coalesce_window(100);
+        for _ in 0..3 {
+            clock.advance(50);
+            session.shift(3..4, 1).unwrap();
+        }
+        clock.advance(200);
+        session.shift(3..4, 1).unwrap();
+        assert_eq!(session.undo().as_deref(), Some("shift"));
+        assert_eq!(timelines(session.script()), vec![10, 20, 30, 43]);
+        session.undo();
+        assert_eq!(timelines(session.script()), original);
+
+        // so is a group, whatever the edits are
+        session.begin_group("nudge");
+        session.shift(3..4, 1).unwrap();
+        session.delete(0..1, Gap::Keep).unwrap();
+        session.end_group();
+        session.shift(2..3, 1).unwrap();
+        assert_eq!(session.undo().as_deref(), Some("shift"));
+        assert_eq!(session.undo().as_deref(), Some("nudge"));
+        assert_eq!(timelines(session.script()), original);
+    }
+}
@@ -0,0 +1,364 @@
+use crate::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalButton, CanonicalKey};
+use crate::canonicalize::Script;
+
+/// A press released sooner than this (in milliseconds) is implausibly short
+pub const MIN_HOLD: i64 = 5;
+
+/// An action performed sooner than this (in milliseconds) after the previous one is implausibly quick
+pub const MIN_GAP: i64 = 2;
+
+/// How serious a [finding](struct.Finding.html) is
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+    /// Unusual, but it may be what the author means
+    Info,
+    /// Likely a mistake, the script can still be acted
+    Warning,
+    /// The action cannot be performed as written
+    Error,
+}
+
+/// The rules of the lint
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Rule {
+    /// A key/button is released without being pressed before
+    ReleaseWithoutPress,
+    /// A key/button is still pressed at the end of the script
+    PressedAtEnd,
+    /// A keyboard action of type [ActionType::Move](../declaration/enum.ActionType.html#variant.Move)
+    KeyboardMove,
+    /// A key that is not known (it cannot be performed)
+    UnknownKey,
+    /// A button that is not known, except for the mouse move (it cannot be performed)
+    UnknownButton,
+    /// A mouse action at a negative position (it may be out of the screen)
+    NegativePosition,
+    /// A press released sooner than [MIN_HOLD](constant.MIN_HOLD.html) milliseconds
+    ShortHold,
+    /// An action performed sooner than [MIN_GAP](constant.MIN_GAP.html) milliseconds after the previous one (the pseudo-actions are not counted)
+    ShortGap,
+}
+
+impl Rule {
+    /// Get the severity of the rule
+    pub fn severity(&self) -> Severity {
+        match self {
+            Rule::KeyboardMove | Rule::UnknownKey | Rule::UnknownButton => Severity::Error,
+            Rule::ReleaseWithoutPress | Rule::PressedAtEnd => Severity::Warning,
+            Rule::NegativePosition | Rule::ShortHold | Rule::ShortGap => Severity::Info,
+        }
+    }
+
+    /// Get what the rule is about
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Rule::ReleaseWithoutPress => "released without being pressed",
+            Rule::PressedAtEnd => "still pressed at the end of the script",
+            Rule::KeyboardMove => "'Move' is not a valid keyboard action type",
+            Rule::UnknownKey => "the key is unknown",
+            Rule::UnknownButton => "the button is unknown",
+            Rule::NegativePosition => "the position is negative",
+            Rule::ShortHold => "released too soon after being pressed",
+            Rule::ShortGap => "performed too soon after the previous action",
+        }
+    }
+}
+
+/// A finding of the lint, see [Script::lint](../struct.Script.html#method.lint)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// The index of the offending action
+    pub index: usize,
+    /// The rule violated
+    pub rule: Rule,
+    /// How serious it is (decided by the rule)
+    pub severity: Severity,
+}
+
+impl Finding {
+    fn new(index: usize, rule: Rule) -> Finding {
+        Finding { index, rule, severity: rule.severity() }
+    }
+}
+
+/// How [Script::load_checked](../struct.Script.html#method.load_checked) deals with the findings of the lint
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Strictness {
+    /// Fail if there is any finding of [Severity::Warning](enum.Severity.html#variant.Warning) or above
+    Strict,
+    /// Load anyway, the findings are returned along with the script
+    Lenient,
+}
+
+/// What is pressed -- a key or a button
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Pressed {
+    Key(CanonicalKey),
+    Button(CanonicalButton),
+}
+
+impl Script {
+    /// Check the consistency of the actions (beyond the self-check), return the findings ordered by the index
+    pub fn lint(&self) -> Vec<Finding> {
+        let mut findings = vec![];
+        // what is pressed, with the index and the timeline of the press
+        let mut pressed: Vec<(Pressed, usize, i64)> = vec![];
+        // the timeline of the previous action (if any), moved back by the explicit waits since then
+        let mut previous: Option<i64> = None;
+
+        for (index, action) in self.actions.iter().enumerate() {
+            match action.action {
+                // the explicit wait holds the playhead, so the following action is performed later than its timeline
+                CanonicalAction::Wait(ms) => previous = previous.map(|v| v - ms),
+                ref other if other.is_pseudo() => {}
+                _ => {
+                    if previous.is_some_and(|v| action.timeline - v < MIN_GAP) {
+                        findings.push(Finding::new(index, Rule::ShortGap));
+                    }
+                    previous = Some(action.timeline);
+                }
+            }
+
+            let (ev, target) = match action.action {
+                CanonicalAction::Keyboard(ev, key) => {
+                    if ev == ActionType::Move {
+                        findings.push(Finding::new(index, Rule::KeyboardMove));
+                        continue;
+                    }
+                    if key == CanonicalKey::Unknown {
+                        findings.push(Finding::new(index, Rule::UnknownKey));
+                    }
+                    (ev, Pressed::Key(key))
+                }
+                CanonicalAction::Mouse(ev, button, (x, y)) => {
+                    if x < 0 || y < 0 {
+                        findings.push(Finding::new(index, Rule::NegativePosition));
+                    }
+                    if ev == ActionType::Move {
+                        continue;
+                    }
+                    if button == CanonicalButton::Unknown {
+                        findings.push(Finding::new(index, Rule::UnknownButton));
+                    }
+                    (ev, Pressed::Button(button))
+                }
+                CanonicalAction::Chord(ref chord) => {
+                    if chord.key == CanonicalKey::Unknown || chord.modifiers.contains(&CanonicalKey::Unknown) {
+                        findings.push(Finding::new(index, Rule::UnknownKey));
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+
+            match ev {
+                ActionType::Press => {
+                    if !pressed.iter().any(|(v, ..)| *v == target) {
+                        pressed.push((target, index, action.timeline));
+                    }
+                }
+                _ => match pressed.iter().position(|(v, ..)| *v == target) {
+                    Some(at) => {
+                        let (_, _, since) = pressed.remove(at);
+                        if action.timeline - since < MIN_HOLD {
+                            findings.push(Finding::new(index, Rule::ShortHold));
+                        }
+                    }
+                    None => findings.push(Finding::new(index, Rule::ReleaseWithoutPress)),
+                },
+            }
+        }
+
+        // what is left is pressed at the end, reported at its press
+        findings.extend(pressed.into_iter().map(|(_, index, _)| Finding::new(index, Rule::PressedAtEnd)));
+        findings.sort_by_key(|v| v.index);
+        findings
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use crate::error::TapeError;
+
+    // region mock script raw
+    const LINTED_SCRIPT_RAW: &str = r##"
+name = "linted"
+ctime = 1695739924720
+duration = 100
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action]
+Keyboard = ["Release", "KeyA"]
+
+[[actions]]
+ctime = 1695739924740
+timeline = 20
+
+[actions.action]
+Keyboard = ["Press", "KeyB"]
+
+[[actions]]
+ctime = 1695739924741
+timeline = 21
+
+[actions.action]
+Keyboard = ["Release", "KeyB"]
+
+[[actions]]
+ctime = 1695739924750
+timeline = 30
+
+[actions.action]
+Keyboard = ["Move", "KeyC"]
+
+[[actions]]
+ctime = 1695739924760
+timeline = 40
+
+[actions.action]
+Mouse = ["Move", "Unknown", [-10, 20]]
+
+[[actions]]
+ctime = 1695739924770
+timeline = 50
+
+[actions.action]
+Mouse = ["Press", "Unknown", [0, 20]]
+
+[[actions]]
+ctime = 1695739924780
+timeline = 60
+
+[actions.action]
+Keyboard = ["Press", "LCtrl"]
+        "##;
+    // endregion
+
+    #[test]
+    fn lint_rules() {
+        let script = Script::load(LINTED_SCRIPT_RAW).unwrap();
+        let findings: Vec<(usize, Rule, Severity)> = script.lint().into_iter().map(|v| (v.index, v.rule, v.severity)).collect();
+        assert_eq!(findings, vec![
+            (0, Rule::ReleaseWithoutPress, Severity::Warning),
+            (2, Rule::ShortGap, Severity::Info),
+            (2, Rule::ShortHold, Severity::Info),
+            (3, Rule::KeyboardMove, Severity::Error),
+            (4, Rule::NegativePosition, Severity::Info),
+            (5, Rule::UnknownButton, Severity::Error),
+            (5, Rule::PressedAtEnd, Severity::Warning),
+            (6, Rule::PressedAtEnd, Severity::Warning),
+        ]);
+    }
+
+    #[test]
+    fn lint_gap() {
+        let raw = r##"
+name = "gaps"
+ctime = 1695739924720
+duration = 50
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action]
+Keyboard = ["Press", "KeyA"]
+
+[[actions]]
+ctime = 1695739924740
+timeline = 20
+
+[actions.action]
+Keyboard = ["Release", "KeyA"]
+
+[[actions]]
+ctime = 1695739924741
+timeline = 21
+
+[actions.action]
+Keyboard = ["Press", "KeyB"]
+
+[[actions]]
+ctime = 1695739924741
+timeline = 21
+
+[actions.action]
+Marker = "typed"
+
+[[actions]]
+ctime = 1695739924750
+timeline = 30
+
+[actions.action]
+Keyboard = ["Release", "KeyB"]
+
+[[actions]]
+ctime = 1695739924750
+timeline = 30
+
+[actions.action]
+Wait = 5
+
+[[actions]]
+ctime = 1695739924751
+timeline = 31
+
+[actions.action]
+Keyboard = ["Press", "KeyC"]
+
+[[actions]]
+ctime = 1695739924760
+timeline = 40
+
+[actions.action]
+Keyboard = ["Release", "KeyC"]
+        "##;
+        // the pseudo-actions are not counted, and the explicit wait widens the gap
+        let script = Script::load(raw).unwrap();
+        assert_eq!(script.lint(), vec![Finding::new(2, Rule::ShortGap)]);
+    }
+
+    #[test]
+    fn load_strictness() {
+        // the lenient mode loads anyway
+        let (script, findings) = Script::load_checked(LINTED_SCRIPT_RAW, Strictness::Lenient).unwrap();
+        assert_eq!(script.actions.len(), 7);
+        assert_eq!(findings.len(), 8);
+
+        // the strict mode fails with the serious ones
+        match Script::load_checked(LINTED_SCRIPT_RAW, Strictness::Strict).unwrap_err() {
+            TapeError::Lint(findings) => {
+                assert_eq!(findings.len(), 5);
+                assert!(findings.iter().all(|v| v.severity >= Severity::Warning));
+            }
+            other => panic!("unexpected error: {}", other),
+        }
+
+        // the info is fine even in the strict mode
+        let raw = r##"
+name = "quick"
+ctime = 1695739924720
+duration = 30
+
+[[actions]]
+ctime = 1695739924740
+timeline = 20
+
+[actions.action]
+Keyboard = ["Press", "KeyB"]
+
+[[actions]]
+ctime = 1695739924740
+timeline = 20
+
+[actions.action]
+Keyboard = ["Release", "KeyB"]
+        "##;
+        let (_, findings) = Script::load_checked(raw, Strictness::Strict).unwrap();
+        assert_eq!(findings, vec![Finding::new(1, Rule::ShortGap), Finding::new(1, Rule::ShortHold)]);
+    }
+}
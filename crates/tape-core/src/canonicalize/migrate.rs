@@ -0,0 +1,116 @@
+use toml::{Table, Value};
+use crate::error::TapeError;
+
+/// The format version of the scripts written by [Script::publish](../struct.Script.html#method.publish)
+pub const FORMAT_VERSION: u32 = 2;
+
+/// The key of the format version in the header of a document
+pub const VERSION_KEY: &str = "format_version";
+
+/// A migration upgrades a document of a version to the next one (the header is not part of the document)
+type Migration = fn(&mut Table) -> Result<(), TapeError>;
+
+/// The chain of migrations, the one at index `i` upgrades a document of version `i + 1` to `i + 2`
+const MIGRATIONS: [Migration; FORMAT_VERSION as usize - 1] = [v1_to_v2];
+
+/// Version 1 is the format before the header was introduced, nothing else is changed in version 2
+fn v1_to_v2(_doc: &mut Table) -> Result<(), TapeError> {
+    Ok(())
+}
+
+/// Get the format version of the document (1 if there is no header)
+pub fn version_of(doc: &Table) -> Result<u32, TapeError> {
+    match doc.get(VERSION_KEY) {
+        None => Ok(1),
+        Some(Value::Integer(version)) if *version >= 1 => Ok(u32::try_from(*version).unwrap_or(u32::MAX)),
+        Some(value) => Err(TapeError::Parse {
+            message: format!("invalid {}: {}", VERSION_KEY, value),
+            line: None,
+            column: None,
+        }),
+    }
+}
+
+/// Check if the format version is supported.
+/// ---
+/// return:
+/// - **Ok(true)**: The version is older than the current one, the document needs [migrating](fn.migrate.html)
+/// - **Ok(false)**: The version is the current one
+/// - **Err(TapeError::UnsupportedVersion)**: The version is newer than this build supports
+pub fn check_version(version: u32) -> Result<bool, TapeError> {
+    match version {
+        v if v > FORMAT_VERSION => Err(TapeError::UnsupportedVersion { found: v, supported: FORMAT_VERSION }),
+        v => Ok(v < FORMAT_VERSION),
+    }
+}
+
+/// Upgrade the document to the current format version through the chain of migrations.
+/// ---
+/// return:
+/// - **Ok(())**: The document is of the current version now
+/// - **Err(TapeError::UnsupportedVersion)**: The document is of a newer version than this build supports
+pub fn migrate(doc: &mut Table) -> Result<(), TapeError> {
+    let version = version_of(doc)?;
+    check_version(version)?;
+
+    for migration in MIGRATIONS[(version - 1) as usize..].iter() {
+        migration(doc)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use crate::canonicalize::Script;
+    use crate::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalKey};
+
+    // region mock script raw
+    /// A script saved before the header was introduced
+    const V1_SCRIPT_RAW: &str = r##"
+name = "mock"
+ctime = 1695739924720
+duration = 60
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action]
+Keyboard = ["Press", "KeyA"]
+
+[[actions]]
+ctime = 1695739924770
+timeline = 50
+
+[actions.action]
+Keyboard = ["Release", "KeyA"]
+        "##;
+    // endregion
+
+    #[test]
+    fn load_v1() {
+        let script = Script::load(V1_SCRIPT_RAW).unwrap();
+        assert_eq!(script.duration, 60);
+        assert_eq!(script.actions[0].action, CanonicalAction::Keyboard(ActionType::Press, CanonicalKey::KeyA));
+
+        // it is published in the current version
+        let raw = script.publish().unwrap();
+        assert!(raw.starts_with(&format!("{} = {}\n", VERSION_KEY, FORMAT_VERSION)));
+        let doc: Table = raw.parse().unwrap();
+        assert_eq!(version_of(&doc).unwrap(), FORMAT_VERSION);
+        assert_eq!(Script::load(&raw).unwrap().actions.len(), 2);
+    }
+
+    #[test]
+    fn load_newer() {
+        let raw = format!("{} = {}\n{}", VERSION_KEY, FORMAT_VERSION + 1, V1_SCRIPT_RAW);
+        assert_eq!(
+            Script::load(&raw).unwrap_err(),
+            TapeError::UnsupportedVersion { found: FORMAT_VERSION + 1, supported: FORMAT_VERSION },
+        );
+
+        let raw = format!("{} = \"two\"\n{}", VERSION_KEY, V1_SCRIPT_RAW);
+        assert!(matches!(Script::load(&raw).unwrap_err(), TapeError::Parse { .. }));
+    }
+}
@@ -1,10 +1,22 @@
-use chrono::Utc;
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
-use crate::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalButton, CanonicalKey};
+use crate::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalButton, CanonicalChord, CanonicalKey};
+use crate::canonicalize::format::Format;
+use crate::canonicalize::lint::{Finding, Severity, Strictness};
+use crate::canonicalize::migrate::{FORMAT_VERSION, VERSION_KEY};
+use crate::clock::{Clock, default_clock, to_rfc3339};
+use crate::error::TapeError;
 
 pub mod declaration;
 pub mod convert_enigo;
 pub mod convert_dq;
+pub mod lint;
+pub mod migrate;
+pub mod format;
+pub mod edit;
+pub mod history;
+pub mod compose;
+pub mod transform;
 
 /// The type of a action to be recorded/acted
 #[derive(Debug, Clone, PartialEq)]
@@ -32,7 +44,7 @@ impl From<String> for ActionSense {
 }
 
 /// An **action** is a single event that can be performed by an [actor](../act/struct.Actor.html)
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Action {
     /// The timestamp of the happening of the action
     pub ctime: i64,
@@ -43,21 +55,77 @@ pub struct Action {
 }
 
 impl Action {
+    /// Create a keyboard action (the time will be stamped once it is added to a script)
     pub fn from_keyboard(ev: ActionType, target: CanonicalKey) -> Action {
         Action {
-            ctime: Utc::now().timestamp_millis(),
+            ctime: 0,
             timeline: 0,
             action: CanonicalAction::Keyboard(ev, target),
         }
     }
 
+    /// Create a mouse action (the time will be stamped once it is added to a script)
     pub fn from_mouse(ev: ActionType, target: CanonicalButton, pos: (i32, i32)) -> Action {
         Action {
-            ctime: Utc::now().timestamp_millis(),
+            ctime: 0,
             timeline: 0,
             action: CanonicalAction::Mouse(ev, target, pos),
         }
     }
+
+    /// Create a key chord action (the time will be stamped once it is added to a script)
+    pub fn from_chord(chord: CanonicalChord) -> Action {
+        Action {
+            ctime: 0,
+            timeline: 0,
+            action: CanonicalAction::Chord(chord),
+        }
+    }
+
+    /// Create a mouse wheel action (the time will be stamped once it is added to a script)
+    pub fn from_scroll(delta: (i32, i32)) -> Action {
+        Action {
+            ctime: 0,
+            timeline: 0,
+            action: CanonicalAction::Scroll(delta),
+        }
+    }
+
+    /// Create a text-typing action (the time will be stamped once it is added to a script)
+    pub fn from_text(text: &str, delay: Option<i64>) -> Action {
+        Action {
+            ctime: 0,
+            timeline: 0,
+            action: CanonicalAction::Text { text: text.to_string(), delay },
+        }
+    }
+
+    /// Create a marker (the time will be stamped once it is added to a script)
+    pub fn from_marker(label: &str) -> Action {
+        Action {
+            ctime: 0,
+            timeline: 0,
+            action: CanonicalAction::Marker(label.to_string()),
+        }
+    }
+
+    /// Create a comment (the time will be stamped once it is added to a script)
+    pub fn from_comment(text: &str) -> Action {
+        Action {
+            ctime: 0,
+            timeline: 0,
+            action: CanonicalAction::Comment(text.to_string()),
+        }
+    }
+
+    /// Create an explicit wait of `ms` milliseconds (the time will be stamped once it is added to a script)
+    pub fn from_wait(ms: i64) -> Action {
+        Action {
+            ctime: 0,
+            timeline: 0,
+            action: CanonicalAction::Wait(ms),
+        }
+    }
 }
 
 /// A **script** is a sequence of [action](struct.Action.html)s recorded by a [recorder](../rec/struct.Recorder.html) for an [actor](../act/struct.Actor.html) to perform
@@ -74,6 +142,9 @@ pub struct Script {
     pub duration: i64,
     /// The actions to perform
     pub actions: Vec<Action>,
+    /// The clock used to stamp the actions, default to the [SystemClock](../clock/struct.SystemClock.html)
+    #[serde(skip, default = "default_clock")]
+    clock: Arc<dyn Clock>,
 }
 
 impl Script {
@@ -81,19 +152,36 @@ impl Script {
     /// Rule:
     /// 1. The actions are sorted by their timeline
     /// 2. The duration of the script should not be less than the timeline of the last action
-    fn self_check(&self) -> Result<(), String> {
+    /// 3. The explicit waits are not negative
+    fn self_check(&self) -> Result<(), TapeError> {
         // check if the actions are sorted by their timeline
         let mut prev = 0;
-        for action in &self.actions {
+        for (index, action) in self.actions.iter().enumerate() {
             if action.timeline < prev {
-                return Err(format!("The actions are not sorted by their timeline!"));
+                return Err(TapeError::SelfCheck {
+                    index,
+                    reason: "The actions are not sorted by their timeline!".to_string(),
+                });
             }
             prev = action.timeline;
+
+            // check if the explicit wait is not negative
+            if let CanonicalAction::Wait(ms) = action.action {
+                if ms < 0 {
+                    return Err(TapeError::SelfCheck {
+                        index,
+                        reason: "The wait is negative!".to_string(),
+                    });
+                }
+            }
         }
 
         // check if the duration of the script is not less than the timeline of the last action
         if self.duration < prev {
-            return Err(format!("The duration of the script is less than the timeline of the last action!"));
+            return Err(TapeError::SelfCheck {
+                index: self.actions.len().saturating_sub(1),
+                reason: "The duration of the script is less than the timeline of the last action!".to_string(),
+            });
         }
 
         Ok(())
@@ -101,59 +189,144 @@ impl Script {
 
     /// Create a empty script
     pub fn empty() -> Script {
-        let t = Utc::now();
+        Script::with_clock(default_clock())
+    }
+
+    /// Create a empty script whose time is told by the given clock
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Script {
+        let t = clock.now();
         Script {
             cursor: 0,
-            name: t.to_rfc3339(),
-            ctime: t.timestamp_millis(),
+            name: to_rfc3339(t),
+            ctime: t,
             duration: 0,
             actions: Vec::new(),
+            clock,
         }
     }
 
+    /// Get the clock used to stamp the actions
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Set the clock used to stamp the actions (the actions already stamped are untouched)
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
     /// Reset the script for coming recording
     pub fn reset(&mut self) {
-        let t = Utc::now();
+        let t = self.clock.now();
         self.cursor = 0;
-        self.name = t.to_rfc3339();
-        self.ctime = t.timestamp_millis();
+        self.name = to_rfc3339(t);
+        self.ctime = t;
         self.duration = 0;
         self.actions.clear();
     }
 
-    /// Load a script from a TOML string.
+    /// Load a script from a TOML string (or a JSON one, which is [detected](format/enum.Format.html#method.detect)).
+    /// The script of an older format version is migrated to the current one (see [migrate](migrate/index.html)).
     /// If the parsing is successful and the self-test passes, the script will be returned,
-    /// otherwise an error message will be returned.
-    pub fn load(raw: &str) -> Result<Script, String> {
-        match toml::from_str::<Script>(raw) {
+    /// otherwise an error will be returned.
+    pub fn load(raw: &str) -> Result<Script, TapeError> {
+        if Format::detect(raw.as_bytes()) == Format::Json {
+            return Script::load_as(raw.as_bytes(), Format::Json);
+        }
+
+        // 1 - migrate the document to the current format version
+        let mut doc = raw.parse::<toml::Table>().map_err(|e| TapeError::from_toml(e, raw))?;
+        let original = doc.clone();
+        migrate::migrate(&mut doc)?;
+
+        // 2 - parse the raw text if nothing is changed by the migration (to locate the errors in it)
+        let parsed = match doc == original {
+            true => toml::from_str::<Script>(raw),
+            false => doc.try_into::<Script>(),
+        };
+        match parsed {
             Ok(script) => {
                 match script.self_check() {
                     Ok(_) => Ok(script),
                     Err(check_err) => Err(check_err),
                 }
             }
-            Err(parse_err) => Err(format!("{}", parse_err)),
+            Err(parse_err) => Err(TapeError::from_toml(parse_err, raw)),
         }
     }
 
+    /// Load a script from a TOML string, then check it with the [lint](#method.lint).
+    /// ---
+    /// return:
+    /// - **Ok((script, findings))**: The script and all the findings of the lint
+    /// - **Err(TapeError::Lint)**: In [Strictness::Strict](lint/enum.Strictness.html#variant.Strict) mode, there are findings of [Severity::Warning](lint/enum.Severity.html#variant.Warning) or above (the ones returned)
+    /// - **Err(..)**: The script cannot be loaded, see [load](#method.load)
+    pub fn load_checked(raw: &str, strictness: Strictness) -> Result<(Script, Vec<Finding>), TapeError> {
+        let script = Script::load(raw)?;
+        let findings = script.lint();
+
+        if strictness == Strictness::Strict {
+            let serious: Vec<Finding> = findings.iter().filter(|v| v.severity >= Severity::Warning).cloned().collect();
+            if !serious.is_empty() {
+                return Err(TapeError::Lint(serious));
+            }
+        }
+
+        Ok((script, findings))
+    }
+
     /// Rename the script
     pub fn rename(&mut self, name: String) {
         self.name = name;
     }
 
-    /// Filter the script to keep only the specified type of actions
+    /// Add the releases of the keys and buttons still pressed at the end of the script (in reverse order, at the end of the timeline),
+    /// so that it does not end in a pressed state
+    pub fn release_held(&mut self) {
+        let mut keys: Vec<CanonicalKey> = vec![];
+        let mut buttons: Vec<CanonicalButton> = vec![];
+        let mut position = (0, 0);
+        for action in self.actions.iter() {
+            match action.action {
+                CanonicalAction::Keyboard(ActionType::Press, key) => if !keys.contains(&key) { keys.push(key) },
+                CanonicalAction::Keyboard(ActionType::Release, key) => keys.retain(|v| *v != key),
+                CanonicalAction::Mouse(ev, button, pos) => {
+                    position = pos;
+                    match ev {
+                        ActionType::Press => if !buttons.contains(&button) { buttons.push(button) },
+                        ActionType::Release => buttons.retain(|v| *v != button),
+                        ActionType::Move => {}
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let timeline = self.actions.last().map_or(self.duration, |v| v.timeline.max(self.duration));
+        let releases = buttons.into_iter().rev().map(|button| CanonicalAction::Mouse(ActionType::Release, button, position))
+            .chain(keys.into_iter().rev().map(|key| CanonicalAction::Keyboard(ActionType::Release, key)));
+        for action in releases.collect::<Vec<_>>() {
+            self.actions.push(Action { ctime: self.ctime + timeline, timeline, action });
+        }
+    }
+
+    /// Filter the script to keep only the specified type of actions (pseudo-actions are always kept)
     pub fn filter(&mut self, sense: ActionSense) {
         self.actions = self.actions.iter().filter(|action| {
+            if action.action.is_pseudo() {
+                return true;
+            }
+
             match sense {
                 ActionSense::Keyboard => {
                     match action.action {
-                        CanonicalAction::Keyboard(..) => true,
+                        CanonicalAction::Keyboard(..) | CanonicalAction::Chord(_) | CanonicalAction::Text { .. } => true,
                         _ => false,
                     }
                 }
                 ActionSense::Mouse => {
                     match action.action {
-                        CanonicalAction::Mouse(..) => true,
+                        CanonicalAction::Mouse(..) | CanonicalAction::Scroll(_) => true,
                         _ => false,
                     }
                 }
@@ -169,10 +342,12 @@ impl Script {
         copy
     }
 
-    /// Publish the script as text
-    pub fn publish(&self) -> Result<String, String> {
+    /// Publish the script as text, headed by the current format version
+    pub fn publish(&self) -> Result<String, TapeError> {
         match self.self_check() {
-            Ok(_) => toml::to_string(self).map_err(|e| format!("{}", e)),
+            Ok(_) => toml::to_string(self)
+                .map(|body| format!("{} = {}\n{}", VERSION_KEY, FORMAT_VERSION, body))
+                .map_err(|e| TapeError::Serialize(e.to_string())),
             Err(check_err) => Err(check_err),
         }
     }
@@ -181,48 +356,192 @@ impl Script {
 #[cfg(test)]
 mod unit_test {
     use super::*;
-    use std::thread;
-    use std::time::Duration;
     use device_query::Keycode;
+    use crate::clock::VirtualClock;
 
-    #[test]
-    fn script_serde() {
-        let mut mv = Script::empty();
-        thread::sleep(Duration::from_secs(1));
+    // region mock script raw
+    /// "a" pressed and released twice, one action every 10 ms, lasting 60 ms (shared by the tests of the submodules)
+    pub(super) const KEYBOARD_SCRIPT_RAW: &str = r##"
+name = "keyboard"
+ctime = 1695739924720
+duration = 60
+
+[[actions]]
+ctime = 1695739924730
+timeline = 10
+
+[actions.action]
+Keyboard = ["Press", "KeyA"]
+
+[[actions]]
+ctime = 1695739924740
+timeline = 20
+
+[actions.action]
+Keyboard = ["Release", "KeyA"]
+
+[[actions]]
+ctime = 1695739924750
+timeline = 30
+
+[actions.action]
+Keyboard = ["Press", "KeyA"]
+
+[[actions]]
+ctime = 1695739924760
+timeline = 40
+
+[actions.action]
+Keyboard = ["Release", "KeyA"]
+        "##;
+
+    /// A drag with the left button (from the move at 0 to the release at 45), lasting 50 ms (shared by the tests of the submodules)
+    pub(super) const MOUSE_SCRIPT_RAW: &str = r##"
+name = "mouse"
+ctime = 1695739930000
+duration = 50
+
+[[actions]]
+ctime = 1695739930000
+timeline = 0
+
+[actions.action]
+Mouse = ["Move", "Unknown", [10, 10]]
+
+[[actions]]
+ctime = 1695739930020
+timeline = 20
+
+[actions.action]
+Mouse = ["Press", "Left", [10, 10]]
+
+[[actions]]
+ctime = 1695739930030
+timeline = 30
+
+[actions.action]
+Mouse = ["Move", "Unknown", [15, 12]]
+
+[[actions]]
+ctime = 1695739930045
+timeline = 45
+
+[actions.action]
+Mouse = ["Release", "Left", [20, 14]]
+        "##;
+    // endregion
+
+    /// Get the timelines of the actions
+    pub(super) fn timelines(script: &Script) -> Vec<i64> {
+        script.actions.iter().map(|v| v.timeline).collect()
+    }
+
+    /// A script recorded on a virtual clock: actions at 1000, 3000, 4000 and 5000 ms
+    fn mock_script(clock: &VirtualClock) -> Script {
+        let mut mv = Script::with_clock(Arc::new(clock.clone()));
+        clock.advance(1000);
         mv.add_keyboard_action(ActionType::Press, Keycode::A);
-        thread::sleep(Duration::from_secs(2));
+        clock.advance(2000);
         mv.add_keyboard_action(ActionType::Release, Keycode::K);
-        thread::sleep(Duration::from_secs(1));
+        clock.advance(1000);
         mv.add_mouse_action(ActionType::Press, 1, (50, 50));
-        thread::sleep(Duration::from_secs(1));
+        clock.advance(1000);
         mv.add_mouse_action(ActionType::Release, 0, (50, 50));
+        mv
+    }
 
-        match toml::to_string(&mv) {
-            Ok(v) => {
-                println!("ok! {}", v);
-                match toml::from_str::<Script>(&v) {
-                    Ok(v) => println!("ok! {:?}", v),
-                    Err(e) => println!("err! {}", e),
-                }
+    #[test]
+    fn script_serde() {
+        let clock = VirtualClock::new(1695739924720);
+        let mut mv = mock_script(&clock);
+        let mut scroll = Action::from_scroll((2, -3));
+        scroll.ctime = clock.now();
+        scroll.timeline = 5000;
+        mv.actions.push(scroll);
+
+        assert_eq!(mv.ctime, 1695739924720);
+        assert_eq!(mv.duration, 5000);
+        assert_eq!(mv.actions.iter().map(|a| a.timeline).collect::<Vec<_>>(), vec![1000, 3000, 4000, 5000, 5000]);
+        assert_eq!(mv.actions[0].ctime, 1695739924720 + 1000);
+
+        let raw = mv.publish().unwrap();
+        assert!(raw.contains("Scroll = [2, -3]"));
+        let loaded = Script::load(&raw).unwrap();
+        assert_eq!(loaded.name, mv.name);
+        assert_eq!(loaded.duration, mv.duration);
+        assert_eq!(
+            loaded.actions.iter().map(|a| (a.timeline, a.action.clone())).collect::<Vec<_>>(),
+            mv.actions.iter().map(|a| (a.timeline, a.action.clone())).collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn chord_parse() {
+        let chord: CanonicalChord = "Ctrl + Shift+t".parse().unwrap();
+        assert_eq!(chord, CanonicalChord::new(vec![CanonicalKey::LCtrl, CanonicalKey::LShift], CanonicalKey::KeyT));
+        assert_eq!(chord.to_string(), "lctrl+lshift+t");
+        assert_eq!(chord.to_string().parse::<CanonicalChord>().unwrap(), chord);
+
+        // a single key is a chord as well
+        assert_eq!("esc".parse::<CanonicalChord>().unwrap(), CanonicalKey::Escape.into());
+
+        // but an empty or unknown part is not
+        for invalid in ["", "ctrl+", "ctrl+foo", "ctrl++t"] {
+            assert_eq!(invalid.parse::<CanonicalChord>().unwrap_err(), TapeError::InvalidChord(invalid.to_string()));
+        }
+
+        // either side of the modifiers triggers the chord
+        assert!(chord.is_triggered(CanonicalKey::KeyT, &[CanonicalKey::RShift, CanonicalKey::LCtrl, CanonicalKey::KeyT]));
+        assert!(!chord.is_triggered(CanonicalKey::KeyT, &[CanonicalKey::LCtrl, CanonicalKey::KeyT]));
+    }
+
+    #[test]
+    fn script_bound_and_reset() {
+        let clock = VirtualClock::new(0);
+        let mut mv = mock_script(&clock);
+
+        // the idle time before binding is kept in the duration
+        clock.advance(1500);
+        assert_eq!(mv.bound().duration, 6500);
+
+        mv.reset();
+        assert_eq!(mv.ctime, 6500);
+        assert_eq!(mv.duration, 0);
+        assert!(mv.actions.is_empty());
+    }
+
+    #[test]
+    fn script_load_error() {
+        // parse errors are located in the raw text
+        let err = Script::load("name = \"x\"\nctime = \"oops\"\nduration = 0\nactions = []\n").unwrap_err();
+        match err {
+            TapeError::Parse { line, column, .. } => {
+                assert_eq!(line, Some(2));
+                assert_eq!(column, Some(9));
             }
-            Err(e) => println!("err! {}", e),
+            _ => panic!("unexpected error: {:?}", err),
         }
+
+        // self-check errors name the offending action
+        let clock = VirtualClock::new(0);
+        let mut mv = mock_script(&clock);
+        mv.actions[2].timeline = 500;
+        assert!(matches!(mv.publish(), Err(TapeError::SelfCheck { index: 2, .. })));
+
+        let mut mv = mock_script(&clock);
+        mv.duration = 4500;
+        assert!(matches!(mv.publish(), Err(TapeError::SelfCheck { index: 3, .. })));
     }
 
     #[test]
     fn script_filter() {
-        let mut mv = Script::empty();
-        thread::sleep(Duration::from_secs(1));
-        mv.add_keyboard_action(ActionType::Press, Keycode::A);
-        thread::sleep(Duration::from_secs(2));
-        mv.add_keyboard_action(ActionType::Release, Keycode::K);
-        thread::sleep(Duration::from_secs(1));
-        mv.add_mouse_action(ActionType::Press, 1, (50, 50));
-        thread::sleep(Duration::from_secs(1));
-        mv.add_mouse_action(ActionType::Release, 0, (50, 50));
+        let clock = VirtualClock::new(0);
+        let mut mv = mock_script(&clock);
 
         mv.filter(ActionSense::Keyboard);
 
-        println!("{:?}", mv);
+        assert_eq!(mv.actions.len(), 2);
+        assert!(mv.actions.iter().all(|a| matches!(a.action, CanonicalAction::Keyboard(..))));
+        assert_eq!(mv.duration, 5000);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,217 @@
+use crate::canonicalize::Script;
+use crate::canonicalize::declaration::{ActionType, CanonicalAction};
+use crate::canonicalize::edit::shift_all;
+
+/// The bounds of the random changes made by [Script::humanize](../struct.Script.html#method.humanize)
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Jitter {
+    /// How much (in milliseconds) the gap between two actions may be lengthened or shortened
+    pub max_delay: i64,
+    /// How far (in pixels, along each axis) the position of a mouse action may be moved
+    pub max_offset: i32,
+}
+
+/// A small random generator (SplitMix64), the same seed gives the same numbers on every platform
+struct SeededRandom(u64);
+
+impl SeededRandom {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// Get a number between `-bound` and `bound` (both inclusive)
+    fn within(&mut self, bound: i64) -> i64 {
+        match bound > 0 {
+            true => (self.next() % (2 * bound as u64 + 1)) as i64 - bound,
+            false => 0,
+        }
+    }
+}
+
+// Collection of methods of Script on 'transform'
+impl Script {
+    /// Strip the idle time before the first action, so that the timeline starts from it (at 0)
+    pub fn trim_leading(&mut self) {
+        if let Some(lead) = self.actions.first().map(|v| v.timeline) {
+            shift_all(&mut self.actions, -lead);
+            self.duration -= lead;
+        }
+    }
+
+    /// Strip the idle time after the last action, so that the script ends with it
+    pub fn trim_trailing(&mut self) {
+        self.duration = self.actions.last().map_or(0, |v| v.timeline);
+    }
+
+    /// Shorten every gap (in milliseconds) longer than `max_gap` to it, including the one before the first action.
+    /// The later actions are brought forward, and so is the end of the script.
+    pub fn cap_gaps(&mut self, max_gap: i64) {
+        let max_gap = max_gap.max(0);
+        let mut prev = 0;
+        let mut cut = 0;
+        for action in self.actions.iter_mut() {
+            let gap = action.timeline - prev;
+            prev = action.timeline;
+            cut += (gap - max_gap).max(0);
+            action.timeline -= cut;
+            action.ctime -= cut;
+        }
+        self.duration -= cut;
+    }
+
+    /// Strip the idle time after the last action, and shorten the gaps longer than `max_gap` (if any), see [cap_gaps](#method.cap_gaps).
+    ///
+    /// If `rebase` is true, the idle time before the first action is stripped as well ([trim_leading](#method.trim_leading)),
+    /// otherwise it is kept (but capped like the other gaps).
+    pub fn trim(&mut self, max_gap: Option<i64>, rebase: bool) {
+        if rebase {
+            self.trim_leading();
+        }
+        if let Some(max_gap) = max_gap {
+            self.cap_gaps(max_gap);
+        }
+        self.trim_trailing();
+    }
+
+    /// Add bounded random changes to the timing and the mouse positions, so that no two replays are identical.
+    /// The same seed makes the same changes, to reproduce a humanized run.
+    ///
+    /// - The gap before each action is lengthened or shortened by [max_delay](transform/struct.Jitter.html#structfield.max_delay) at most,
+    ///   but never below 0, so that the actions are never reordered (the simultaneous ones are kept together).
+    /// - The position of each mouse action is moved by [max_offset](transform/struct.Jitter.html#structfield.max_offset) at most (but never below 0),
+    ///   and the moves while a button is pressed share the offset of the press, so that a click stays a click.
+    ///
+    /// The idle time after the last action is kept.
+    pub fn humanize(&mut self, jitter: Jitter, seed: u64) {
+        let mut random = SeededRandom(seed);
+        let max_offset = jitter.max_offset.max(0) as i64;
+        let tail = self.duration - self.actions.last().map_or(0, |v| v.timeline);
+
+        let mut prev = 0;
+        let mut moved = 0;
+        let mut pressed = 0;
+        let mut held_offset = (0, 0);
+        for action in self.actions.iter_mut() {
+            // 1 - jitter the gap before the action
+            let gap = action.timeline - prev;
+            prev = action.timeline;
+            if gap > 0 {
+                moved = (moved + random.within(jitter.max_delay)).max(moved - gap);
+            }
+            action.timeline += moved;
+            action.ctime += moved;
+
+            // 2 - jitter the position of the mouse action
+            if let CanonicalAction::Mouse(ev, _, ref mut pos) = action.action {
+                let offset = match (ev, pressed) {
+                    (ActionType::Move, 0) | (ActionType::Press, 0) => {
+                        (random.within(max_offset) as i32, random.within(max_offset) as i32)
+                    }
+                    _ => held_offset,
+                };
+                match ev {
+                    ActionType::Press => pressed += 1,
+                    ActionType::Release => pressed = (pressed - 1).max(0),
+                    ActionType::Move => {}
+                }
+                held_offset = offset;
+                *pos = ((pos.0 + offset.0).max(0), (pos.1 + offset.1).max(0));
+            }
+        }
+
+        self.duration = self.actions.last().map_or(self.duration, |v| v.timeline + tail);
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use crate::canonicalize::declaration::CanonicalButton;
+    use crate::canonicalize::unit_test::{KEYBOARD_SCRIPT_RAW, MOUSE_SCRIPT_RAW, timelines};
+
+    #[test]
+    fn transform_trim() {
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        script.trim_leading();
+        assert_eq!(timelines(&script), vec![0, 10, 20, 30]);
+        assert_eq!(script.duration, 50);
+        assert!(script.actions.iter().all(|v| v.ctime == script.ctime + v.timeline));
+        script.trim_trailing();
+        assert_eq!(script.duration, 30);
+
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        script.cap_gaps(5);
+        assert_eq!(timelines(&script), vec![5, 10, 15, 20]);
+        assert_eq!(script.duration, 40);
+        assert!(script.actions.iter().all(|v| v.ctime == script.ctime + v.timeline));
+
+        // all at once, with or without the rebase
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        script.trim(Some(5), false);
+        assert_eq!(timelines(&script), vec![5, 10, 15, 20]);
+        assert_eq!(script.duration, 20);
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        script.trim(Some(5), true);
+        assert_eq!(timelines(&script), vec![0, 5, 10, 15]);
+        assert_eq!(script.duration, 15);
+
+        let mut script = Script::empty();
+        script.duration = 9000;
+        script.trim(Some(5), true);
+        assert_eq!(script.duration, 0);
+    }
+
+    #[test]
+    fn transform_humanize() {
+        // the keys are pressed along with the drag, some of the actions at the same point
+        let mut script = Script::load(KEYBOARD_SCRIPT_RAW).unwrap();
+        script.merge(&Script::load(MOUSE_SCRIPT_RAW).unwrap(), 20);
+        let jitter = Jitter { max_delay: 3, max_offset: 3 };
+
+        let mut humanized = script.clone();
+        humanized.humanize(jitter, 42);
+        let after = timelines(&humanized);
+
+        // the gaps are jittered within the bounds, never below 0 (the simultaneous actions are kept together)
+        let gaps = |v: &[i64]| v.windows(2).map(|w| w[1] - w[0]).collect::<Vec<_>>();
+        let (before, after) = (gaps(&timelines(&script)), gaps(&after));
+        assert_ne!(before, after);
+        assert!(before.iter().zip(&after).all(|(b, a)| *a >= 0 && (a - b).abs() <= jitter.max_delay));
+        assert_eq!((after[1], after[4]), (0, 0));
+        assert_eq!(humanized.duration - humanized.actions[7].timeline, 5);
+
+        // the press, the drag and the release share the offset
+        let offsets: Vec<(i32, i32)> = script.actions.iter().zip(&humanized.actions).filter_map(|(a, b)| match (&a.action, &b.action) {
+            (CanonicalAction::Mouse(_, _, from), CanonicalAction::Mouse(_, _, to)) => Some((to.0 - from.0, to.1 - from.1)),
+            _ => None,
+        }).collect();
+        assert_eq!(offsets.len(), 4);
+        assert!(offsets.iter().all(|(x, y)| x.abs() <= 3 && y.abs() <= 3));
+        assert_eq!(offsets[1], offsets[2]);
+        assert_eq!(offsets[2], offsets[3]);
+
+        // the same seed makes the same changes
+        let mut again = script.clone();
+        again.humanize(jitter, 42);
+        assert_eq!(again.actions, humanized.actions);
+        let mut other = script.clone();
+        other.humanize(jitter, 7);
+        assert_ne!(other.actions, humanized.actions);
+
+        // the positions at the corner never go below 0
+        let mut script = Script::load(MOUSE_SCRIPT_RAW).unwrap();
+        script.actions[0].action = CanonicalAction::Mouse(ActionType::Move, CanonicalButton::Unknown, (0, 0));
+        for seed in 0..20 {
+            let mut humanized = script.clone();
+            humanized.humanize(jitter, seed);
+            match humanized.actions[0].action {
+                CanonicalAction::Mouse(_, _, (x, y)) => assert!(x >= 0 && y >= 0 && x <= 3 && y <= 3),
+                _ => unreachable!(),
+            }
+        }
+    }
+}
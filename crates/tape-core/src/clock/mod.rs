@@ -0,0 +1,137 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use chrono::{TimeZone, Utc};
+
+/// A **clock** tells the time to [script](../canonicalize/struct.Script.html)s, [recorder](../record/struct.Recorder.html)s and [actor](../act/struct.Actor.html)s.
+pub trait Clock: Send + Sync + Debug {
+    /// The current time in milliseconds since the unix epoch
+    fn now(&self) -> i64;
+
+    /// Let `ms` milliseconds pass on the clock (do nothing if `ms` is not positive).
+    ///
+    /// A real clock does it by calling `block` with the duration to block the current thread,
+    /// and `block` may return early (e.g. when woken up by a [Mission](../mission/struct.Mission.html)), so the caller should check the time again.
+    fn wait(&self, ms: i64, block: &mut dyn FnMut(Duration));
+
+    /// Block the current thread for `ms` milliseconds (do nothing if `ms` is not positive)
+    fn sleep(&self, ms: i64) {
+        self.wait(ms, &mut |timeout| thread::sleep(timeout));
+    }
+}
+
+/// Get the default clock ([SystemClock](struct.SystemClock.html))
+pub fn default_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock::new())
+}
+
+/// Format a timestamp (in milliseconds) as a RFC 3339 string
+pub fn to_rfc3339(timestamp: i64) -> String {
+    match Utc.timestamp_millis_opt(timestamp).single() {
+        Some(t) => t.to_rfc3339(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// The clock of the system.
+///
+/// The time is anchored to the wall clock once created and then goes on monotonically.
+#[derive(Debug, Clone)]
+pub struct SystemClock {
+    /// The wall clock time when the clock is created
+    anchor_ms: i64,
+    /// The monotonic time when the clock is created
+    anchor: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock {
+            anchor_ms: Utc::now().timestamp_millis(),
+            anchor: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> i64 {
+        self.anchor_ms + self.anchor.elapsed().as_millis() as i64
+    }
+
+    fn wait(&self, ms: i64, block: &mut dyn FnMut(Duration)) {
+        if ms > 0 { block(Duration::from_millis(ms as u64)); }
+    }
+}
+
+/// A clock whose time only goes on when it is told to, for deterministic tests.
+///
+/// - Use [advance](#method.advance) to move the time forward by hand.
+/// - Waiting/sleeping on it returns immediately, with the time moved forward by the duration.
+///
+/// Clones share the same time.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualClock {
+    now: Arc<AtomicI64>,
+}
+
+impl VirtualClock {
+    /// Create a clock starting at the given time
+    pub fn new(start: i64) -> VirtualClock {
+        VirtualClock { now: Arc::new(AtomicI64::new(start)) }
+    }
+
+    /// Move the time forward by `ms` milliseconds
+    pub fn advance(&self, ms: i64) {
+        if ms > 0 { self.now.fetch_add(ms, Ordering::SeqCst); }
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> i64 {
+        self.now.load(Ordering::SeqCst)
+    }
+
+    fn wait(&self, ms: i64, _block: &mut dyn FnMut(Duration)) {
+        self.advance(ms);
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    #[test]
+    fn virtual_clock() {
+        let clock = VirtualClock::new(1000);
+        let shared = clock.clone();
+
+        clock.advance(500);
+        assert_eq!(shared.now(), 1500);
+
+        shared.sleep(250);
+        assert_eq!(clock.now(), 1750);
+
+        // time never goes back
+        clock.advance(-100);
+        clock.sleep(-100);
+        assert_eq!(clock.now(), 1750);
+    }
+
+    #[test]
+    fn system_clock() {
+        let clock = SystemClock::new();
+        let t1 = clock.now();
+        clock.sleep(20);
+        let t2 = clock.now();
+        assert!(t2 - t1 >= 20);
+        assert!((clock.now() - Utc::now().timestamp_millis()).abs() < 1000);
+    }
+}
@@ -0,0 +1,124 @@
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use crate::canonicalize::declaration::{CanonicalButton, CanonicalKey};
+use crate::canonicalize::lint::Finding;
+
+/// The errors of tape
+#[derive(Debug, Clone, PartialEq)]
+pub enum TapeError {
+    /// The script cannot be parsed
+    /// - message: the message of the parser
+    /// - line/column: the position (1-based) where the error happens, if known
+    Parse { message: String, line: Option<usize>, column: Option<usize> },
+    /// The script does not pass the self-check
+    /// - index: the index of the offending action (the last action for the rule of duration)
+    /// - reason: the rule violated
+    SelfCheck { index: usize, reason: String },
+    /// The script does not pass the lint in the strict mode
+    /// - the findings of warning or above (ordered by the index, never empty)
+    Lint(Vec<Finding>),
+    /// The script is of a newer format version than the supported one
+    UnsupportedVersion { found: u32, supported: u32 },
+    /// The range (of indexes or of the timeline) to edit is reversed or out of the script
+    InvalidRange { start: i64, end: i64 },
+    /// The script cannot be serialized
+    Serialize(String),
+    /// The journal cannot be created or read
+    Journal(String),
+    /// The task may never stop since there is no stop signal
+    NoStopSignal,
+    /// The recorder/actor is already working
+    AlreadyWorking,
+    /// The acting is not paused (to be moved by seeking)
+    NotPaused,
+    /// The speed of acting is not a positive finite number
+    InvalidSpeed(f64),
+    /// The chord (e.g. a signal) has an empty part or a part that is not a known key
+    InvalidChord(String),
+    /// The name of the format is none of "toml", "json" and "binary"
+    UnknownFormat(String),
+    /// The key has no counterpart in the backend
+    UnmappableKey(CanonicalKey),
+    /// The button has no counterpart in the backend
+    UnmappableButton(CanonicalButton),
+}
+
+impl TapeError {
+    /// Create a parse error from the error of toml, locating it in the raw text
+    pub(crate) fn from_toml(err: toml::de::Error, raw: &str) -> TapeError {
+        let (line, column) = match err.span() {
+            Some(span) => {
+                let (line, column) = locate(raw, span.start);
+                (Some(line), Some(column))
+            }
+            None => (None, None),
+        };
+
+        TapeError::Parse { message: err.message().to_string(), line, column }
+    }
+}
+
+/// Get the line and column (1-based) of the byte offset in the text
+fn locate(raw: &str, offset: usize) -> (usize, usize) {
+    let before = &raw[..offset.min(raw.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |v| v.chars().count()) + 1;
+    (line, column)
+}
+
+impl Display for TapeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TapeError::Parse { message, line: Some(line), column: Some(column) } =>
+                write!(f, "failed to parse the script at line {}, column {}: {}", line, column, message),
+            TapeError::Parse { message, .. } =>
+                write!(f, "failed to parse the script: {}", message),
+            TapeError::SelfCheck { index, reason } =>
+                write!(f, "the script failed the self-check at action {}: {}", index, reason),
+            TapeError::Lint(findings) => match findings.first() {
+                Some(first) => write!(f, "the script failed the lint with {} finding(s), the first at action {}: {}", findings.len(), first.index, first.rule.describe()),
+                None => write!(f, "the script failed the lint"),
+            },
+            TapeError::UnsupportedVersion { found, supported } =>
+                write!(f, "the script is of format version {}, newer than the supported version {}", found, supported),
+            TapeError::InvalidRange { start, end } =>
+                write!(f, "the range {}..{} is out of the script", start, end),
+            TapeError::Serialize(message) =>
+                write!(f, "failed to serialize the script: {}", message),
+            TapeError::Journal(message) =>
+                write!(f, "failed to access the journal: {}", message),
+            TapeError::NoStopSignal =>
+                write!(f, "no stop signal is set, the task may never stop"),
+            TapeError::AlreadyWorking =>
+                write!(f, "the worker is already working"),
+            TapeError::NotPaused =>
+                write!(f, "the acting is not paused"),
+            TapeError::InvalidSpeed(speed) =>
+                write!(f, "the speed {} is not a positive number", speed),
+            TapeError::InvalidChord(chord) =>
+                write!(f, "the chord '{}' is invalid, every part of it should be a known key", chord),
+            TapeError::UnknownFormat(name) =>
+                write!(f, "the format '{}' is unknown, it should be one of 'toml', 'json' and 'binary'", name),
+            TapeError::UnmappableKey(key) =>
+                write!(f, "the key '{}' cannot be mapped", key.to_string()),
+            TapeError::UnmappableButton(button) =>
+                write!(f, "the button '{}' cannot be mapped", button.to_string()),
+        }
+    }
+}
+
+impl Error for TapeError {}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+
+    #[test]
+    fn locate_offset() {
+        let raw = "name = 1\nctime = \"x\"\n";
+        assert_eq!(locate(raw, 0), (1, 1));
+        assert_eq!(locate(raw, 9), (2, 1));
+        assert_eq!(locate(raw, 17), (2, 9));
+        assert_eq!(locate(raw, 1000), (3, 1));
+    }
+}
@@ -1,3 +1,7 @@
 pub mod act;
+pub mod backend;
 pub mod canonicalize;
+pub mod clock;
+pub mod error;
+pub mod mission;
 pub mod record;
@@ -0,0 +1,296 @@
+use std::sync::{Condvar, Mutex, MutexGuard};
+use crate::clock::Clock;
+use crate::error::TapeError;
+
+/// The phase of a mission
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Phase {
+    /// Nothing is running
+    Idle,
+    /// The worker is running
+    Working,
+    /// The worker is paused, waiting to be resumed (or stopped)
+    Paused,
+    /// The worker is asked to stop, but has not stopped yet
+    Stopping,
+}
+
+/// A **mission** is the lifecycle of a worker ([recorder](../record/struct.Recorder.html) or [actor](../act/struct.Actor.html)),
+/// shared between the worker thread and the ones controlling it.
+///
+/// Every change of the phase wakes up the ones waiting on the mission immediately.
+#[derive(Debug)]
+pub struct Mission {
+    state: Mutex<State>,
+    changed: Condvar,
+}
+
+#[derive(Debug)]
+struct State {
+    phase: Phase,
+    /// How many times the worker has been [nudged](struct.Mission.html#method.nudge)
+    nudges: u64,
+}
+
+impl Default for Mission {
+    fn default() -> Self {
+        Mission::new()
+    }
+}
+
+impl Mission {
+    /// Create an idle mission
+    pub fn new() -> Mission {
+        Mission {
+            state: Mutex::new(State { phase: Phase::Idle, nudges: 0 }),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    fn set(&self, mut state: MutexGuard<'_, State>, to: Phase) {
+        state.phase = to;
+        self.changed.notify_all();
+    }
+
+    /// Get the current phase
+    pub fn phase(&self) -> Phase {
+        self.lock().phase
+    }
+
+    /// Whether the worker is running (including the paused one and the one asked to stop but not stopped yet)
+    pub fn is_working(&self) -> bool {
+        self.phase() != Phase::Idle
+    }
+
+    /// Whether the worker should go on
+    pub fn should_work(&self) -> bool {
+        self.phase() == Phase::Working
+    }
+
+    /// Whether the worker is paused
+    pub fn is_paused(&self) -> bool {
+        self.phase() == Phase::Paused
+    }
+
+    /// Whether the worker is asked to stop but has not stopped yet
+    pub fn is_stopping(&self) -> bool {
+        self.phase() == Phase::Stopping
+    }
+
+    /// Start the mission, fail if it is not idle
+    pub fn begin(&self) -> Result<(), TapeError> {
+        let state = self.lock();
+        if state.phase != Phase::Idle {
+            return Err(TapeError::AlreadyWorking);
+        }
+        self.set(state, Phase::Working);
+        Ok(())
+    }
+
+    /// Ask the worker to stop (do nothing if it is not working)
+    pub fn finish(&self) {
+        let state = self.lock();
+        if state.phase == Phase::Working || state.phase == Phase::Paused {
+            self.set(state, Phase::Stopping);
+        }
+    }
+
+    /// Ask the worker to pause (do nothing if it is not working or already paused)
+    pub fn pause(&self) {
+        let state = self.lock();
+        if state.phase == Phase::Working {
+            self.set(state, Phase::Paused);
+        }
+    }
+
+    /// Ask the paused worker to go on (do nothing if it is not paused)
+    pub fn resume(&self) {
+        let state = self.lock();
+        if state.phase == Phase::Paused {
+            self.set(state, Phase::Working);
+        }
+    }
+
+    /// Pause the working worker, or resume the paused one
+    pub fn toggle_pause(&self) {
+        let state = self.lock();
+        match state.phase {
+            Phase::Working => self.set(state, Phase::Paused),
+            Phase::Paused => self.set(state, Phase::Working),
+            _ => {}
+        }
+    }
+
+    /// Wake up the sleeping worker without changing the phase (e.g. when the time it waits for has changed)
+    pub fn nudge(&self) {
+        let mut state = self.lock();
+        state.nudges += 1;
+        self.changed.notify_all();
+    }
+
+    /// Mark the mission as done, called by the worker once it has stopped
+    pub fn done(&self) {
+        let state = self.lock();
+        self.set(state, Phase::Idle);
+    }
+
+    /// Block until the worker is asked to stop
+    pub fn wait_stop(&self) {
+        let _state = self.changed.wait_while(self.lock(), |s| s.phase == Phase::Working || s.phase == Phase::Paused).unwrap();
+    }
+
+    /// Block while the worker is paused.
+    ///
+    /// Return whether the worker should go on (false if it is asked to stop in the meantime).
+    pub fn wait_resume(&self) -> bool {
+        let state = self.changed.wait_while(self.lock(), |s| s.phase == Phase::Paused).unwrap();
+        state.phase == Phase::Working
+    }
+
+    /// Block until the worker has stopped
+    pub fn wait_idle(&self) {
+        let _state = self.changed.wait_while(self.lock(), |s| s.phase != Phase::Idle).unwrap();
+    }
+
+    /// Let `ms` milliseconds pass on the clock, unless the worker is asked to stop (or pause), or is nudged in the meantime.
+    ///
+    /// Return whether the time has passed without interruption.
+    pub fn sleep(&self, clock: &dyn Clock, ms: i64) -> bool {
+        let deadline = clock.now() + ms;
+        let mut state = Some(self.lock());
+        let nudges = state.as_ref().unwrap().nudges;
+
+        loop {
+            let current = state.as_ref().unwrap();
+            if current.phase != Phase::Working || current.nudges != nudges {
+                return false;
+            }
+
+            let remaining = deadline - clock.now();
+            if remaining <= 0 {
+                return true;
+            }
+
+            clock.wait(remaining, &mut |timeout| {
+                let guard = state.take().unwrap();
+                state = Some(self.changed.wait_timeout(guard, timeout).unwrap().0);
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+    use std::time::Duration;
+    use crate::clock::{SystemClock, VirtualClock};
+
+    #[test]
+    fn lifecycle() {
+        let mission = Mission::new();
+        assert!(!mission.is_working());
+
+        mission.begin().unwrap();
+        assert_eq!(mission.begin().unwrap_err(), TapeError::AlreadyWorking);
+        assert!(mission.should_work());
+
+        mission.finish();
+        assert_eq!(mission.phase(), Phase::Stopping);
+        assert!(mission.is_working());
+        assert_eq!(mission.begin().unwrap_err(), TapeError::AlreadyWorking);
+
+        mission.done();
+        assert!(!mission.is_working());
+        mission.begin().unwrap();
+    }
+
+    #[test]
+    fn pause_and_resume() {
+        let mission = Arc::new(Mission::new());
+
+        // nothing to pause
+        mission.pause();
+        assert_eq!(mission.phase(), Phase::Idle);
+
+        mission.begin().unwrap();
+        mission.toggle_pause();
+        assert!(mission.is_paused());
+        assert!(mission.is_working());
+        assert!(!mission.should_work());
+        assert!(!mission.sleep(&VirtualClock::new(0), 100));
+
+        let tmp1 = Arc::clone(&mission);
+        let waiter = thread::spawn(move || tmp1.wait_resume());
+        mission.resume();
+        assert!(waiter.join().unwrap());
+        assert!(mission.should_work());
+
+        // a paused mission can be stopped
+        mission.pause();
+        let tmp1 = Arc::clone(&mission);
+        let waiter = thread::spawn(move || tmp1.wait_resume());
+        mission.finish();
+        assert!(!waiter.join().unwrap());
+        assert_eq!(mission.phase(), Phase::Stopping);
+    }
+
+    #[test]
+    fn sleep_virtual() {
+        let clock = VirtualClock::new(0);
+        let mission = Mission::new();
+        mission.begin().unwrap();
+
+        assert!(mission.sleep(&clock, 10_000));
+        assert_eq!(clock.now(), 10_000);
+
+        mission.finish();
+        assert!(!mission.sleep(&clock, 10_000));
+        assert_eq!(clock.now(), 10_000);
+    }
+
+    #[test]
+    fn sleep_nudged() {
+        let mission = Arc::new(Mission::new());
+        mission.begin().unwrap();
+
+        let tmp1 = Arc::clone(&mission);
+        let sleeper = thread::spawn(move || tmp1.sleep(&SystemClock::new(), 60_000));
+        while !sleeper.is_finished() {
+            mission.nudge();
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        // the nudged worker wakes up but goes on
+        assert!(!sleeper.join().unwrap());
+        assert!(mission.should_work());
+    }
+
+    #[test]
+    fn sleep_interrupted() {
+        let mission = Arc::new(Mission::new());
+        mission.begin().unwrap();
+
+        let tmp1 = Arc::clone(&mission);
+        let sleeper = thread::spawn(move || {
+            let clock = SystemClock::new();
+            let start = clock.now();
+            let working = tmp1.sleep(&clock, 60_000);
+            (working, clock.now() - start)
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        mission.finish();
+        let (working, slept) = sleeper.join().unwrap();
+
+        // woken up by 'finish' (a timeout would return true, a minute later)
+        assert!(!working);
+        assert!(slept < 30_000);
+        assert_eq!(mission.phase(), Phase::Stopping);
+    }
+}
@@ -0,0 +1,202 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use crate::canonicalize::{Action, Script};
+use crate::canonicalize::migrate::{self, FORMAT_VERSION};
+use crate::error::TapeError;
+
+/// An entry of the journal, each one is written as a line of JSON
+#[derive(Serialize, Deserialize, Debug, Clone)]
+enum Entry {
+    /// The first line, what the script is
+    Header { format_version: u32, name: String, ctime: i64 },
+    /// An action is recorded
+    Action(Action),
+    /// The last action is taken back
+    Retract,
+    /// The recording is finished, with the duration of the script
+    Bound(i64),
+}
+
+/// A **journal** is the file a script is written to as it is being recorded,
+/// so that it can be [recovered](fn.recover.html) if the recording never finishes (e.g. the process dies).
+///
+/// Each entry is appended as a line once it happens, so a crash loses at most the line being written.
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    file: File,
+    /// How many actions are in the journal (the retracted ones excluded)
+    count: usize,
+    /// The first failure of writing, nothing is written after it
+    error: Option<String>,
+}
+
+impl Journal {
+    /// Create the journal of the script (truncate the file if it exists), with the header written
+    pub fn create(path: impl AsRef<Path>, script: &Script) -> Result<Journal, TapeError> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::create(&path).map_err(|e| TapeError::Journal(e.to_string()))?;
+        let mut journal = Journal { path, file, count: 0, error: None };
+
+        journal.write(&Entry::Header { format_version: FORMAT_VERSION, name: script.name.clone(), ctime: script.ctime });
+        match journal.error.take() {
+            Some(message) => Err(TapeError::Journal(message)),
+            None => Ok(journal),
+        }
+    }
+
+    /// Get where the journal is
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Get how many actions are in the journal (the retracted ones excluded)
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Get the failure of writing, if any
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Append an entry as a line, return whether it is written
+    fn write(&mut self, entry: &Entry) -> bool {
+        if self.error.is_some() {
+            return false;
+        }
+
+        let written = serde_json::to_vec(entry)
+            .map_err(|e| e.to_string())
+            .and_then(|mut line| {
+                line.push(b'\n');
+                self.file.write_all(&line).map_err(|e| e.to_string())
+            });
+        match written {
+            Ok(_) => true,
+            Err(message) => {
+                self.error = Some(message);
+                false
+            }
+        }
+    }
+
+    /// Append an action, return whether it is written
+    pub(crate) fn append(&mut self, action: &Action) -> bool {
+        let written = self.write(&Entry::Action(action.clone()));
+        if written {
+            self.count += 1;
+        }
+        written
+    }
+
+    /// Take back the last action, return whether it is written
+    pub(crate) fn retract(&mut self) -> bool {
+        let written = self.write(&Entry::Retract);
+        if written {
+            self.count = self.count.saturating_sub(1);
+        }
+        written
+    }
+
+    /// Mark the recording as finished, and flush the journal to the disk
+    pub(crate) fn bind(&mut self, duration: i64) -> bool {
+        self.write(&Entry::Bound(duration)) && self.file.sync_data().is_ok()
+    }
+}
+
+/// Rebuild the script from the journal at the path, see [recover_from](fn.recover_from.html)
+pub fn recover(path: impl AsRef<Path>) -> Result<Script, TapeError> {
+    let file = File::open(path).map_err(|e| TapeError::Journal(e.to_string()))?;
+    recover_from(BufReader::new(file))
+}
+
+/// Rebuild the script from a journal, which may be truncated.
+///
+/// Everything after the first broken line (the one being written when the recording was interrupted) is ignored.
+/// If the journal is not finished, the duration of the script lasts until the last action.
+/// ---
+/// return:
+/// - **Ok(Script)**: The script recorded until the journal ends
+/// - **Err(TapeError::Parse)**: The header is missing or broken
+/// - **Err(TapeError::UnsupportedVersion)**: The journal is of a newer format version
+pub fn recover_from(reader: impl BufRead) -> Result<Script, TapeError> {
+    let mut entries = reader.split(b'\n')
+        .map_while(|line| line.ok())
+        .map_while(|line| serde_json::from_slice::<Entry>(&line).ok());
+
+    // 1 - read the header
+    let mut script = match entries.next() {
+        Some(Entry::Header { format_version, name, ctime }) => {
+            migrate::check_version(format_version)?;
+            let mut script = Script::empty();
+            script.name = name;
+            script.ctime = ctime;
+            script
+        }
+        _ => return Err(TapeError::Parse { message: "the journal has no header".to_string(), line: Some(1), column: None }),
+    };
+
+    // 2 - replay the entries
+    let mut bound = None;
+    for entry in entries {
+        match entry {
+            Entry::Action(action) => script.actions.push(action),
+            Entry::Retract => { script.actions.pop(); }
+            Entry::Bound(duration) => bound = Some(duration),
+            Entry::Header { .. } => break,
+        }
+    }
+
+    let last = script.actions.last().map_or(0, |v| v.timeline);
+    script.duration = bound.unwrap_or(last).max(last);
+    Ok(script)
+}
+
+#[cfg(test)]
+mod unit_test {
+    use super::*;
+    use crate::canonicalize::declaration::{ActionType, CanonicalKey};
+
+    #[test]
+    fn journal_recover() {
+        let path = std::env::temp_dir().join(format!("tape-journal-{}.jsonl", std::process::id()));
+        let mut script = Script::empty();
+        script.name = "journal".to_string();
+        script.ctime = 1695739924720;
+
+        let mut journal = Journal::create(&path, &script).unwrap();
+        for (timeline, ev) in [(10, ActionType::Press), (20, ActionType::Release), (30, ActionType::Press)] {
+            let mut action = Action::from_keyboard(ev, CanonicalKey::KeyA);
+            action.timeline = timeline;
+            action.ctime = script.ctime + timeline;
+            assert!(journal.append(&action));
+        }
+        assert!(journal.retract());
+
+        // an unfinished journal lasts until the last action
+        let recovered = recover(&path).unwrap();
+        assert_eq!(recovered.name, "journal");
+        assert_eq!(recovered.ctime, 1695739924720);
+        assert_eq!(recovered.actions.iter().map(|v| v.timeline).collect::<Vec<_>>(), vec![10, 20]);
+        assert_eq!(recovered.duration, 20);
+
+        // a finished one lasts until it is bound
+        assert!(journal.bind(50));
+        assert_eq!(recover(&path).unwrap().duration, 50);
+        std::fs::remove_file(&path).unwrap();
+
+        // the line being written is dropped
+        let truncated = "{\"Header\":{\"format_version\":2,\"name\":\"x\",\"ctime\":0}}\n\
+            {\"Action\":{\"ctime\":5,\"timeline\":5,\"action\":{\"Keyboard\":[\"Press\",\"KeyA\"]}}}\n\
+            {\"Action\":{\"ctime\":9,\"timeli";
+        let recovered = recover_from(truncated.as_bytes()).unwrap();
+        assert_eq!(recovered.actions.len(), 1);
+        assert_eq!(recovered.duration, 5);
+
+        // but the header is required
+        assert!(matches!(recover_from("{\"Header\":{\"form".as_bytes()).unwrap_err(), TapeError::Parse { .. }));
+    }
+}
@@ -1,17 +1,30 @@
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::thread;
-use std::time::Duration;
-use chrono::Utc;
-use device_query::{DeviceEvents, DeviceQuery, DeviceState, Keycode, MouseButton};
-use crate::canonicalize::declaration::{ActionType, CanonicalKey};
+use std::thread::{self, JoinHandle};
+use device_query::{Keycode, MouseButton};
+use crate::backend::{EventCallback, EventSource, HeldKeys, InputEvent};
+use crate::backend::native::DeviceSource;
+use crate::clock::{Clock, default_clock};
+use crate::error::TapeError;
+use crate::mission::Mission;
+use crate::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalButton, CanonicalChord, CanonicalKey};
 use crate::canonicalize::{Action, ActionSense, Script};
+use crate::record::journal::Journal;
+
+pub mod journal;
 
 // Collection of methods of Script on 'record'
 impl Script {
-    /// Add an action to the script
-    fn add_action(&mut self, mut action: Action) {
-        // calculate the elapsed time since the creation of the script
-        let elapsed = action.ctime - self.ctime;
+    /// Add an action to the script (stamped with the current time of the script's clock)
+    fn add_action(&mut self, action: Action) {
+        self.add_action_after_pause(action, 0);
+    }
+
+    /// Add an action to the script, leaving the time paused (in milliseconds) out of its timeline
+    fn add_action_after_pause(&mut self, mut action: Action, paused: i64) {
+        action.ctime = self.clock().now();
+        // calculate the elapsed time since the creation of the script (except the time paused)
+        let elapsed = action.ctime - self.ctime - paused;
         // update the timeline of the action
         action.timeline = elapsed;
         // update the duration of the script
@@ -33,36 +46,321 @@ impl Script {
     /// Bind actions into a script (use the creation time of the script to the current time as the duration)
     /// Return a copy of the script
     pub fn bound(&mut self) -> Script {
-        self.duration = Utc::now().timestamp_millis() - self.ctime;
+        self.duration = self.clock().now() - self.ctime;
         self.clone()
     }
 }
 
-/// The gap between two loops
-const LOOP_GAP: u64 = 100;
+/// A script being recorded, whose timeline leaves out the time paused
+#[derive(Debug)]
+struct Take {
+    script: Script,
+    /// Whether the script is still being recorded (not bound yet)
+    open: bool,
+    /// The time paused so far (in milliseconds)
+    paused: i64,
+    /// The time (of the clock) when the current pause begins, if paused
+    paused_at: Option<i64>,
+    /// Whether to release what is still pressed once the script is bound
+    auto_release: bool,
+    /// Where the actions are written to as they are recorded, if any
+    journal: Option<Journal>,
+    /// How many of the actions in memory (from the first one) are already in the journal
+    written: usize,
+}
+
+impl Take {
+    fn new() -> Take {
+        Take { script: Script::empty(), open: false, paused: 0, paused_at: None, auto_release: false, journal: None, written: 0 }
+    }
+
+    /// Reset the script for a new recording
+    fn reset(&mut self, clock: Arc<dyn Clock>, auto_release: bool) {
+        self.script.set_clock(clock);
+        self.script.reset();
+        self.open = true;
+        self.paused = 0;
+        self.paused_at = None;
+        self.auto_release = auto_release;
+        self.journal = None;
+        self.written = 0;
+    }
+
+    /// Write the script to a journal from now on
+    fn keep_journal(&mut self, path: &Path) -> Result<(), TapeError> {
+        self.journal = Some(Journal::create(path, &self.script)?);
+        self.written = 0;
+        Ok(())
+    }
+
+    /// Write the actions not in the journal yet, return whether all of them are written
+    /// (once the journal fails, the actions are kept in memory instead)
+    fn write(&mut self) -> bool {
+        match self.journal.as_mut() {
+            Some(journal) => {
+                while self.written < self.script.actions.len() {
+                    if !journal.append(&self.script.actions[self.written]) {
+                        return false;
+                    }
+                    self.written += 1;
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Write the actions to the journal, then drop them from memory except the trailing presses (which may be [retracted](#method.retract))
+    fn commit(&mut self) {
+        if self.write() {
+            let kept = self.script.actions.iter().rev()
+                .take_while(|v| matches!(v.action, CanonicalAction::Keyboard(ActionType::Press, _)))
+                .count();
+            self.script.actions.drain(..self.script.actions.len() - kept);
+            self.written = kept;
+        }
+    }
+
+    /// Get all the actions recorded so far, reading back the ones dropped from memory
+    /// (fail with [TapeError::Journal](../error/enum.TapeError.html#variant.Journal) if the journal cannot be read back)
+    fn all_actions(&self) -> Result<Vec<Action>, TapeError> {
+        match self.journal.as_ref() {
+            Some(journal) => {
+                let mut actions = journal::recover(journal.path())?.actions;
+                actions.extend_from_slice(&self.script.actions[self.written..]);
+                Ok(actions)
+            }
+            None => Ok(self.script.actions.clone()),
+        }
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Add an action to the script (it is dropped if paused)
+    fn add(&mut self, action: Action) {
+        if !self.is_paused() {
+            self.script.add_action_after_pause(action, self.paused);
+            self.commit();
+        }
+    }
+
+    /// Pause the recording, with a "pause" marker added
+    fn pause(&mut self) {
+        if !self.is_paused() {
+            self.script.add_action_after_pause(Action::from_marker("pause"), self.paused);
+            self.paused_at = self.script.actions.last().map(|marker| marker.ctime);
+            self.commit();
+        }
+    }
+
+    /// Resume the recording, with a "resume" marker added (at the same point of the timeline as the pause)
+    fn resume(&mut self) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused += self.script.clock().now() - paused_at;
+            self.script.add_action_after_pause(Action::from_marker("resume"), self.paused);
+            self.commit();
+        }
+    }
+
+    /// Take back the trailing presses of the modifiers of the signal (they are part of the signal rather than the script)
+    fn retract(&mut self, signal: &CanonicalChord) {
+        while let Some(&CanonicalAction::Keyboard(ActionType::Press, key)) = self.script.actions.last().map(|v| &v.action) {
+            if !signal.has_modifier(key) {
+                break;
+            }
+            self.script.actions.pop();
+            if self.written > self.script.actions.len() {
+                self.written -= 1;
+                if let Some(journal) = self.journal.as_mut() {
+                    journal.retract();
+                }
+            }
+        }
+    }
+
+    /// Get the duration of the script recorded so far (except the time paused)
+    fn duration(&self) -> i64 {
+        let now = self.paused_at.unwrap_or_else(|| self.script.clock().now());
+        now - self.script.ctime - self.paused
+    }
+
+    /// Bind the actions into a script, return a copy of it.
+    ///
+    /// Fail if the journal cannot be read back, the take is left as it is then
+    /// (the actions dropped from memory can still be [recovered](journal/fn.recover.html) from the journal).
+    fn bound(&mut self) -> Result<Script, TapeError> {
+        if self.open {
+            // read back the actions in the journal (it is finished with the releases)
+            if self.journal.is_some() {
+                let unwritten = self.script.actions.len() - self.written;
+                self.script.actions = self.all_actions()?;
+                self.written = self.script.actions.len() - unwritten;
+            }
+
+            self.script.duration = self.duration();
+            if self.auto_release {
+                self.script.release_held();
+            }
+            if self.write() {
+                if let Some(journal) = self.journal.as_mut() {
+                    journal.bind(self.script.duration);
+                }
+            }
+
+            self.journal = None;
+            self.open = false;
+        }
+        Ok(self.script.clone())
+    }
+
+    /// Get a copy of the script recorded so far, whose duration lasts until now if it is still being recorded.
+    ///
+    /// The actions dropped from memory are not read back here, the copy holds only the ones after them,
+    /// along with where the journal is and how many actions are in it, see [read_back](fn.read_back.html).
+    fn snapshot(&self) -> (Script, Option<(PathBuf, usize)>) {
+        let mut script = self.script.clone();
+        if !self.open {
+            return (script, None);
+        }
+
+        script.duration = self.duration();
+        match self.journal.as_ref() {
+            Some(journal) => {
+                script.actions.drain(..self.written);
+                (script, Some((journal.path().to_path_buf(), journal.count())))
+            }
+            None => (script, None),
+        }
+    }
+}
+
+/// Put the first `count` actions in the journal before the ones of the snapshot
+/// (the journal is read without locking the take, so that the recording goes on in the meantime).
+///
+/// Fail with [TapeError::Journal](../error/enum.TapeError.html#variant.Journal) if the journal cannot be read back.
+fn read_back(mut script: Script, path: &Path, count: usize) -> Result<Script, TapeError> {
+    let mut actions = journal::recover(path)?.actions;
+    actions.truncate(count);
+    actions.append(&mut script.actions);
+    script.actions = actions;
+    Ok(script)
+}
+
+/// Pause or resume the recording (toggle if `pause` is None)
+fn switch_pause(mission: &Mission, take: &Mutex<Take>, pause: Option<bool>) {
+    let mut take = take.lock().unwrap();
+    let pause = pause.unwrap_or(!take.is_paused());
+
+    if pause && mission.should_work() {
+        mission.pause();
+        take.pause();
+    } else if !pause && mission.is_paused() {
+        mission.resume();
+        take.resume();
+    }
+}
+
+/// Create the listener of a recording.
+///
+/// It pushes the events of the given type to the script, stops the recording once the stop signal is pressed,
+/// and pauses/resumes the recording once the pause signal is pressed.
+fn make_listener(
+    record_type: ActionSense,
+    stop_signal: Option<CanonicalChord>,
+    pause_signal: Option<CanonicalChord>,
+    mission: Arc<Mission>,
+    take: Arc<Mutex<Take>>,
+) -> EventCallback {
+    let held = HeldKeys::default();
+    // the keys of the pause signal whose releases are yet to come (they are part of the signal rather than the script)
+    let swallowed: Mutex<Vec<CanonicalKey>> = Mutex::new(vec![]);
+    Arc::new(move |ev: &InputEvent| {
+        let held = held.track(ev);
+        let action = match *ev {
+            InputEvent::KeyDown(key) | InputEvent::KeyUp(key) => {
+                if let InputEvent::KeyUp(_) = ev {
+                    let mut swallowed = swallowed.lock().unwrap();
+                    if let Some(index) = swallowed.iter().position(|v| *v == key) {
+                        swallowed.remove(index);
+                        return;
+                    }
+                }
+
+                // if the stop signal is pressed, stop the recording
+                if let Some(signal) = stop_signal.as_ref().filter(|v| v.is_triggered(key, &held)) {
+                    take.lock().unwrap().retract(signal);
+                    mission.finish();
+                    return;
+                }
+
+                // if the pause signal is pressed, pause/resume the recording
+                if let Some(signal) = pause_signal.as_ref().filter(|v| v.is_triggered(key, &held)) {
+                    if let InputEvent::KeyDown(_) = ev {
+                        take.lock().unwrap().retract(signal);
+                        switch_pause(&mission, &take, None);
+                        swallowed.lock().unwrap().extend(held.iter().filter(|v| **v == key || signal.has_modifier(**v)));
+                    }
+                    return;
+                }
+
+                if !record_type.with_keyboard() { return; }
+
+                match ev {
+                    InputEvent::KeyDown(_) => Action::from_keyboard(ActionType::Press, key),
+                    _ => Action::from_keyboard(ActionType::Release, key),
+                }
+            }
+            InputEvent::MouseDown(btn, pos) if record_type.with_mouse() => Action::from_mouse(ActionType::Press, btn, pos),
+            InputEvent::MouseUp(btn, pos) if record_type.with_mouse() => Action::from_mouse(ActionType::Release, btn, pos),
+            InputEvent::MouseMove(pos) if record_type.with_mouse() => Action::from_mouse(ActionType::Move, CanonicalButton::Unknown, pos),
+            InputEvent::MouseScroll(delta) if record_type.with_mouse() => Action::from_scroll(delta),
+            _ => return,
+        };
+
+        // push the action to the script
+        take.lock().unwrap().add(action);
+    })
+}
 
 /// A **recorder** is a person who records your [action](../canonicalize/struct.Action.html)s into a [script](../canonicalize/struct.Script.html) for an [actor](../act/struct.Actor.html) to perform.
 ///
 /// ---
-/// - The recorder does not internally determine whether you call `record/record_sync` again on the working recorder. You need to judge by yourself to avoid unexpected behavior (use [is_working](#method.is_working)).
+/// - Calling `record/record_sync` again on the working recorder will get a [TapeError::AlreadyWorking](../error/enum.TapeError.html#variant.AlreadyWorking).
 pub struct Recorder {
     /// The type of the action to be recorded
     record_type: ActionSense,
-    /// The key that stops the recording
-    stop_signal: Option<CanonicalKey>,
-    /// A flag indicating whether the recorder is working
-    mission_guard: Arc<Mutex<bool>>,
+    /// The key (or chord) that stops the recording
+    stop_signal: Option<CanonicalChord>,
+    /// The key (or chord) that pauses/resumes the recording
+    pause_signal: Option<CanonicalChord>,
+    /// Whether to add the releases of what is still pressed once the recording stops
+    auto_release: bool,
+    /// Where to write the script as it is being recorded, if any
+    journal: Option<PathBuf>,
+    /// The lifecycle of the recording
+    mission: Arc<Mission>,
     /// The script being recorded
-    script: Arc<Mutex<Script>>,
+    take: Arc<Mutex<Take>>,
+    /// Where the events are listened from
+    source: Arc<dyn EventSource>,
+    /// The clock used to stamp the actions
+    clock: Arc<dyn Clock>,
 }
 
 impl Default for Recorder {
     fn default() -> Self {
         Recorder {
             record_type: ActionSense::Keyboard,
-            stop_signal: Some(CanonicalKey::Escape),
-            mission_guard: Arc::new(Mutex::new(false)),
-            script: Arc::new(Mutex::new(Script::empty())),
+            stop_signal: Some(CanonicalKey::Escape.into()),
+            pause_signal: None,
+            auto_release: false,
+            journal: None,
+            mission: Arc::new(Mission::new()),
+            take: Arc::new(Mutex::new(Take::new())),
+            source: Arc::new(DeviceSource),
+            clock: default_clock(),
         }
     }
 }
@@ -75,16 +373,21 @@ impl Recorder {
 
     /// Check whether the actor is working
     pub fn is_working(&self) -> bool {
-        *self.mission_guard.lock().unwrap()
+        self.mission.is_working()
     }
 
     /// Create a new recorder
-    pub fn new(record_type: ActionSense, stop_signal: Option<CanonicalKey>) -> Self {
+    pub fn new(record_type: ActionSense, stop_signal: Option<CanonicalChord>) -> Self {
         Recorder {
             record_type,
             stop_signal,
-            mission_guard: Arc::new(Mutex::new(false)),
-            script: Arc::new(Mutex::new(Script::empty())),
+            pause_signal: None,
+            auto_release: false,
+            journal: None,
+            mission: Arc::new(Mission::new()),
+            take: Arc::new(Mutex::new(Take::new())),
+            source: Arc::new(DeviceSource),
+            clock: default_clock(),
         }
     }
 
@@ -95,146 +398,145 @@ impl Recorder {
         self.record_type = record_type;
     }
 
-    /// Set the key that stops the recording
+    /// Set the key (or chord, e.g. "ctrl+shift+q") that stops the recording
     ///
     /// This has no effect on the current recording. (The signal is copied once [record](#method.record) is called.)
-    pub fn set_stop_signal(&mut self, stop_signal: Option<CanonicalKey>) {
+    pub fn set_stop_signal(&mut self, stop_signal: Option<CanonicalChord>) {
         self.stop_signal = stop_signal;
     }
 
+    /// Set the key (or chord) that pauses the recording, and resumes it once pressed again (default to None)
+    ///
+    /// This has no effect on the current recording. (The signal is copied once [record](#method.record) is called.)
+    pub fn set_pause_signal(&mut self, pause_signal: Option<CanonicalChord>) {
+        self.pause_signal = pause_signal;
+    }
+
+    /// Set whether to add the releases of the keys and buttons still pressed once the recording stops (default to false),
+    /// see [Script::release_held](../canonicalize/struct.Script.html#method.release_held)
+    ///
+    /// This has no effect on the current recording. (The option is copied once [record](#method.record) is called.)
+    pub fn set_auto_release(&mut self, auto_release: bool) {
+        self.auto_release = auto_release;
+    }
+
+    /// Set the file to write the script to as it is being recorded (default to None),
+    /// so that it can be [recovered](journal/fn.recover.html) if the recording never finishes.
+    /// Only the last few actions are kept in memory during the recording then.
+    ///
+    /// This has no effect on the current recording. (The path is copied once [record](#method.record) is called.)
+    pub fn set_journal(&mut self, journal: Option<PathBuf>) {
+        self.journal = journal;
+    }
+
+    /// Set where the events are listened from, default to the devices ([DeviceSource](../backend/native/struct.DeviceSource.html))
+    ///
+    /// This has no effect on the current recording. (The listener is set once [record](#method.record) is called.)
+    pub fn set_source(&mut self, source: Arc<dyn EventSource>) {
+        self.source = source;
+    }
+
+    /// Set the clock used to stamp the actions, default to the [SystemClock](../clock/struct.SystemClock.html)
+    ///
+    /// This has no effect on the current recording. (The clock is handed to the script once [record](#method.record) is called.)
+    pub fn set_clock(&mut self, clock: Arc<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// Start the mission and prepare a session, fail if the recorder is already working
+    fn begin(&self) -> Result<Session, TapeError> {
+        self.mission.begin()?;
+
+        // reset the script for a new recording
+        let mut take = self.take.lock().unwrap();
+        take.reset(Arc::clone(&self.clock), self.auto_release);
+        if let Some(path) = self.journal.as_ref() {
+            if let Err(err) = take.keep_journal(path) {
+                take.open = false;
+                self.mission.done();
+                return Err(err);
+            }
+        }
+        drop(take);
+
+        Ok(Session {
+            mission: Arc::clone(&self.mission),
+            take: Arc::clone(&self.take),
+            source: Arc::clone(&self.source),
+            listener: make_listener(
+                self.record_type.clone(),
+                self.stop_signal.clone(),
+                self.pause_signal.clone(),
+                Arc::clone(&self.mission),
+                Arc::clone(&self.take),
+            ),
+        })
+    }
+
     /// Start recording
-    /// (asynchronous, you can use [finish](#method.finish) to interrupt the recording).
+    /// (asynchronous, you can use [finish](#method.finish) or the returned handle to interrupt the recording).
     ///
     /// This will run in a separate thread, so it will not block the main thread.
-    /// On the other hand, you may need to wait in the main thread for the recording to finish.
+    /// On the other hand, you may need to [join](struct.RecordingHandle.html#method.join) the returned handle to wait for the recording to finish.
     /// ---
     /// **on_finish**: a callback function that will be called when the recording is finished
     /// - If set to None, do nothing when the recording is finished
-    /// - If set to Some(f), call f(script) when the recording is finished
+    /// - If set to Some(f), call f(script) when the recording is finished (not called if the [journal](#method.set_journal) cannot be read back, [join](struct.RecordingHandle.html#method.join) the handle for the error)
+    /// ---
+    /// return:
+    /// - **Ok(RecordingHandle)**: The recording is started, see [RecordingHandle](struct.RecordingHandle.html)
+    /// - **Err(TapeError::AlreadyWorking)**: The recorder is already working
+    /// - **Err(TapeError::Journal)**: The [journal](#method.set_journal) cannot be created
     /// ---
     /// If you want to use synchronous recording, please call [record_sync](#method.record_sync).
-    pub fn record(&self, on_finish: Option<Box<dyn FnOnce(Script) + Send>>) {
-        // set the working flag
-        *self.mission_guard.lock().unwrap() = true;
-        self.script.lock().unwrap().reset();
-
-        let record_type = self.record_type.clone();
-        let stop_signal = self.stop_signal.clone();
-        let mission_guard = Arc::clone(&self.mission_guard);
-        let script = Arc::clone(&self.script);
+    pub fn record(&self, on_finish: Option<Box<dyn FnOnce(Script) + Send>>) -> Result<RecordingHandle, TapeError> {
+        let session = self.begin()?;
 
         // start the recording thread
-        thread::spawn(move || {
-            let ds = DeviceState::new();
-
-            // several guards
-            let _guard_kd;
-            let _guard_ku;
-            let _guard_md;
-            let _guard_mu;
-            let _guard_mm;
-            let _guard_quit;
-
-            // keyboard events
-            if record_type.with_keyboard() {
-                // keydown listener
-                let tmp1 = Arc::clone(&mission_guard);
-                let tmp2 = stop_signal.clone();
-                let tmp3 = Arc::clone(&script);
-                _guard_kd = ds.on_key_down(move |key| {
-                    // if the stop signal is pressed, stop the recording
-                    if tmp2.is_some_and(|v| &v == key) {
-                        *tmp1.lock().unwrap() = false;
-                        return;
-                    }
-
-                    // push the action to the script
-                    tmp3.lock().unwrap().add_keyboard_action(ActionType::Press, *key);
-                });
-
-                // keyup listener
-                let tmp1 = Arc::clone(&mission_guard);
-                let tmp2 = stop_signal.clone();
-                let tmp3 = Arc::clone(&script);
-                _guard_ku = ds.on_key_up(move |key| {
-                    // if the stop signal is pressed, stop the recording
-                    if tmp2.is_some_and(|v| &v == key) {
-                        *tmp1.lock().unwrap() = false;
-                        return;
-                    }
-
-                    // push the action to the script
-                    tmp3.lock().unwrap().add_keyboard_action(ActionType::Release, *key);
-                });
-            } else {
-                // if the recorder does not record keyboard events,
-                // we still need to listen to the stop signal
-                // keydown listener
-                let tmp1 = Arc::clone(&mission_guard);
-                let tmp2 = stop_signal.clone();
-                _guard_quit = ds.on_key_down(move |key| {
-                    // if the stop signal is pressed, stop the recording
-                    if tmp2.is_some_and(|v| &v == key) {
-                        *tmp1.lock().unwrap() = false;
-                        return;
-                    }
-                });
-            }
+        let thread = thread::spawn(move || {
+            let bound = session.run();
 
-            // mouse events
-            if record_type.with_mouse() {
-                // mousedown listener
-                let tmp1 = DeviceState::new();
-                let tmp2 = Arc::clone(&script);
-                _guard_md = ds.on_mouse_down(move |btn| {
-                    tmp2.lock().unwrap().add_mouse_action(
-                        ActionType::Press, *btn,
-                        tmp1.get_mouse().coords,
-                    );
-                });
-
-                // mouseup listener
-                let tmp1 = DeviceState::new();
-                let tmp2 = Arc::clone(&script);
-                _guard_mu = ds.on_mouse_up(move |btn| {
-                    tmp2.lock().unwrap().add_mouse_action(
-                        ActionType::Release, *btn,
-                        tmp1.get_mouse().coords,
-                    );
-                });
-
-                // mousemove listener
-                let tmp1 = Arc::clone(&script);
-                _guard_mm = ds.on_mouse_move(move |pos| {
-                    tmp1.lock().unwrap().add_mouse_action(
-                        ActionType::Move, 0,
-                        *pos,
-                    );
-                });
-            }
-
-            // do recording until the mission is finished
-            while *mission_guard.lock().unwrap() {
-                // sleep for a while to avoid too frequent checking
-                thread::sleep(Duration::from_millis(LOOP_GAP));
+            // call the callback function if it is set (and the script is bound)
+            if let (Some(f), Ok(script)) = (on_finish, &bound) {
+                f(script.clone());
             };
 
-            // bind the script
-            let script_copy = script.lock().unwrap().bound();
-
-            // call the callback function if it is set
-            if let Some(f) = on_finish {
-                f(script_copy);
-            };
+            bound
         });
+
+        Ok(RecordingHandle {
+            mission: Arc::clone(&self.mission),
+            take: Arc::clone(&self.take),
+            thread,
+        })
     }
 
     /// Interrupt the recording.
     ///
-    /// If you want to manipulate the result, please set the **on_finish** when calling [record](#method.record).
+    /// If you want to manipulate the result, please [join](struct.RecordingHandle.html#method.join) the handle returned by [record](#method.record),
+    /// or set the **on_finish** when calling it.
     pub fn finish(&self) {
-        // set the working flag to false
-        *self.mission_guard.lock().unwrap() = false;
+        self.mission.finish();
+    }
+
+    /// Check whether the recording is paused
+    pub fn is_paused(&self) -> bool {
+        self.mission.is_paused()
+    }
+
+    /// Pause the recording (it will do nothing if the recorder is not recording).
+    ///
+    /// The events are dropped during the pause, and the time paused is left out of the timeline of the later actions.
+    /// A "pause" [marker](../canonicalize/declaration/enum.CanonicalAction.html#variant.Marker) is added to the script.
+    pub fn pause(&self) {
+        switch_pause(&self.mission, &self.take, Some(true));
+    }
+
+    /// Resume the paused recording (it will do nothing if the recording is not paused).
+    ///
+    /// A "resume" [marker](../canonicalize/declaration/enum.CanonicalAction.html#variant.Marker) is added to the script.
+    pub fn resume(&self) {
+        switch_pause(&self.mission, &self.take, Some(false));
     }
 
     /// Start recording
@@ -242,134 +544,457 @@ impl Recorder {
     /// ---
     /// return:
     /// - **Ok(Script)**: The script being recorded
-    /// - **Err(())**: If you have not set the stop signal, this will return a Err(()).
+    /// - **Err(TapeError::NoStopSignal)**: If you have not set the stop signal, this will return a Err.
     /// This is by design rather than a bug (image a situation where you have not set the stop signal, then the recorder will never stop).
+    /// - **Err(TapeError::AlreadyWorking)**: The recorder is already working
+    /// - **Err(TapeError::Journal)**: The [journal](#method.set_journal) cannot be created, or cannot be read back once the recording is finished
     /// ---
     /// If you want to use asynchronous recording, please call [record](#method.record).
-    pub fn record_sync(&self) -> Result<Script, ()> {
+    pub fn record_sync(&self) -> Result<Script, TapeError> {
         if self.stop_signal.is_none() {
-            return Err(());
+            return Err(TapeError::NoStopSignal);
         }
 
-        // set the working flag
-        *self.mission_guard.lock().unwrap() = true;
-        self.script.lock().unwrap().reset();
-
-        let record_type = self.record_type.clone();
-        let stop_signal = self.stop_signal.clone().unwrap();
-        let mission_guard = Arc::clone(&self.mission_guard);
-        let script = Arc::clone(&self.script);
-
-        let ds = DeviceState::new();
-
-        // several guards
-        let _guard_kd;
-        let _guard_ku;
-        let _guard_md;
-        let _guard_mu;
-        let _guard_mm;
-        let _guard_quit;
-
-        // keyboard events
-        if record_type.with_keyboard() {
-            // keydown listener
-            let tmp1 = Arc::clone(&mission_guard);
-            let tmp2 = stop_signal.clone();
-            let tmp3 = Arc::clone(&script);
-            _guard_kd = ds.on_key_down(move |key| {
-                // if the stop signal is pressed, stop the recording
-                if &tmp2 == key {
-                    *tmp1.lock().unwrap() = false;
-                    return;
-                }
-
-                // push the action to the script
-                tmp3.lock().unwrap().add_keyboard_action(ActionType::Press, *key);
-            });
-
-            // keyup listener
-            let tmp1 = Arc::clone(&mission_guard);
-            let tmp2 = stop_signal.clone();
-            let tmp3 = Arc::clone(&script);
-            _guard_ku = ds.on_key_up(move |key| {
-                // if the stop signal is pressed, stop the recording
-                if &tmp2 == key {
-                    *tmp1.lock().unwrap() = false;
-                    return;
-                }
+        self.begin()?.run()
+    }
+}
 
-                // push the action to the script
-                tmp3.lock().unwrap().add_keyboard_action(ActionType::Release, *key);
-            });
-        } else {
-            // if the recorder does not record keyboard events,
-            // we still need to listen to the stop signal
-            // keydown listener
-            let tmp1 = Arc::clone(&mission_guard);
-            let tmp2 = stop_signal.clone();
-            _guard_quit = ds.on_key_down(move |key| {
-                // if the stop signal is pressed, stop the recording
-                if &tmp2 == key {
-                    *tmp1.lock().unwrap() = false;
-                    return;
-                }
-            });
-        }
+/// Everything a recording needs, taken from the recorder once the recording starts.
+///
+/// Shared by [record](struct.Recorder.html#method.record) (in a separate thread) and [record_sync](struct.Recorder.html#method.record_sync) (in place).
+struct Session {
+    mission: Arc<Mission>,
+    take: Arc<Mutex<Take>>,
+    source: Arc<dyn EventSource>,
+    listener: EventCallback,
+}
 
-        // mouse events
-        if record_type.with_mouse() {
-            // mousedown listener
-            let tmp1 = DeviceState::new();
-            let tmp2 = Arc::clone(&script);
-            _guard_md = ds.on_mouse_down(move |btn| {
-                tmp2.lock().unwrap().add_mouse_action(
-                    ActionType::Press, *btn,
-                    tmp1.get_mouse().coords,
-                );
-            });
-
-            // mouseup listener
-            let tmp1 = DeviceState::new();
-            let tmp2 = Arc::clone(&script);
-            _guard_mu = ds.on_mouse_up(move |btn| {
-                tmp2.lock().unwrap().add_mouse_action(
-                    ActionType::Release, *btn,
-                    tmp1.get_mouse().coords,
-                );
-            });
-
-            // mousemove listener
-            let tmp1 = Arc::clone(&script);
-            _guard_mm = ds.on_mouse_move(move |pos| {
-                tmp1.lock().unwrap().add_mouse_action(
-                    ActionType::Move, 0,
-                    *pos,
-                );
-            });
-        }
+impl Session {
+    /// Record until the mission is asked to stop, then mark the mission as done.
+    ///
+    /// Return a copy of the bound script, see [Take::bound](struct.Take.html#method.bound).
+    fn run(self) -> Result<Script, TapeError> {
+        let guard = self.source.listen(self.listener);
 
         // do recording until the mission is finished
-        while *mission_guard.lock().unwrap() {
-            // sleep for a while to avoid too frequent checking
-            thread::sleep(Duration::from_millis(LOOP_GAP));
-        };
+        self.mission.wait_stop();
+        drop(guard);
 
         // bind the script
-        let r = Ok(script.lock().unwrap().bound());
-        r
+        let bound = self.take.lock().unwrap().bound();
+        self.mission.done();
+        bound
+    }
+}
+
+/// The handle of a recording started by [record](struct.Recorder.html#method.record).
+///
+/// Dropping the handle does not stop the recording, it just detaches from it.
+#[derive(Debug)]
+pub struct RecordingHandle {
+    /// The lifecycle of the recording
+    mission: Arc<Mission>,
+    /// The script being recorded
+    take: Arc<Mutex<Take>>,
+    /// The recording thread, which returns the bound script
+    thread: JoinHandle<Result<Script, TapeError>>,
+}
+
+impl RecordingHandle {
+    /// Check whether the recording is still going on
+    pub fn is_working(&self) -> bool {
+        !self.thread.is_finished()
+    }
+
+    /// Interrupt the recording (it will do nothing if the recording is already finished)
+    ///
+    /// Use [join](#method.join) to get the result.
+    pub fn stop(&self) {
+        self.mission.finish();
+    }
+
+    /// Check whether the recording is paused
+    pub fn is_paused(&self) -> bool {
+        self.mission.is_paused()
+    }
+
+    /// Pause the recording, see [Recorder::pause](struct.Recorder.html#method.pause)
+    pub fn pause(&self) {
+        switch_pause(&self.mission, &self.take, Some(true));
+    }
+
+    /// Resume the paused recording, see [Recorder::resume](struct.Recorder.html#method.resume)
+    pub fn resume(&self) {
+        switch_pause(&self.mission, &self.take, Some(false));
+    }
+
+    /// Get a copy of the script recorded so far, without ending the recording.
+    ///
+    /// The duration of the copy lasts until now if the recording is still going on.
+    /// Fail with [TapeError::Journal](../error/enum.TapeError.html#variant.Journal) if the [journal](struct.Recorder.html#method.set_journal) cannot be read back.
+    pub fn snapshot(&self) -> Result<Script, TapeError> {
+        let (script, journal) = self.take.lock().unwrap().snapshot();
+        match journal {
+            Some((path, count)) => read_back(script, &path, count),
+            None => Ok(script),
+        }
+    }
+
+    /// Block until the recording is finished (by the stop signal or [stop](#method.stop)), and get the script recorded.
+    ///
+    /// Fail with [TapeError::Journal](../error/enum.TapeError.html#variant.Journal) if the [journal](struct.Recorder.html#method.set_journal) cannot be read back.
+    pub fn join(self) -> Result<Script, TapeError> {
+        match self.thread.join() {
+            Ok(bound) => bound,
+            // the callback panicked, but the script is already bound
+            Err(_) => Ok(self.take.lock().unwrap().script.clone()),
+        }
     }
 }
 
 #[cfg(test)]
 mod unit_test {
     use super::*;
+    use std::time::Duration;
+    use crate::backend::mock::MockSource;
+    use crate::canonicalize::declaration::CanonicalAction;
+    use crate::clock::VirtualClock;
+
+    #[test]
+    fn record_sync_mock() {
+        let mut recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
+        recorder.set_source(Arc::new(MockSource::scripted(vec![
+            InputEvent::KeyDown(CanonicalKey::KeyA),
+            InputEvent::MouseMove((10, 10)),
+            InputEvent::KeyUp(CanonicalKey::KeyA),
+            InputEvent::KeyDown(CanonicalKey::Escape),
+        ])));
+
+        let script = recorder.record_sync().unwrap();
+        let actions: Vec<CanonicalAction> = script.actions.into_iter().map(|a| a.action).collect();
+        assert_eq!(actions, vec![
+            CanonicalAction::Keyboard(ActionType::Press, CanonicalKey::KeyA),
+            CanonicalAction::Keyboard(ActionType::Release, CanonicalKey::KeyA),
+        ]);
+        assert!(!recorder.is_working());
+
+        // a recorder without stop signal never stops
+        recorder.set_stop_signal(None);
+        assert_eq!(recorder.record_sync().unwrap_err(), TapeError::NoStopSignal);
+        recorder.set_stop_signal(Some(CanonicalKey::Escape.into()));
+
+        // record both keyboard and mouse
+        recorder.set_record_type(ActionSense::Both);
+        recorder.set_source(Arc::new(MockSource::scripted(vec![
+            InputEvent::MouseMove((10, 10)),
+            InputEvent::MouseDown(CanonicalButton::Left, (10, 10)),
+            InputEvent::MouseUp(CanonicalButton::Left, (10, 10)),
+            InputEvent::MouseScroll((0, -3)),
+            InputEvent::KeyDown(CanonicalKey::KeyB),
+            InputEvent::KeyDown(CanonicalKey::Escape),
+        ])));
+
+        let script = recorder.record_sync().unwrap();
+        let actions: Vec<CanonicalAction> = script.actions.into_iter().map(|a| a.action).collect();
+        assert_eq!(actions, vec![
+            CanonicalAction::Mouse(ActionType::Move, CanonicalButton::Unknown, (10, 10)),
+            CanonicalAction::Mouse(ActionType::Press, CanonicalButton::Left, (10, 10)),
+            CanonicalAction::Mouse(ActionType::Release, CanonicalButton::Left, (10, 10)),
+            CanonicalAction::Scroll((0, -3)),
+            CanonicalAction::Keyboard(ActionType::Press, CanonicalKey::KeyB),
+        ]);
+    }
+
+    #[test]
+    fn record_chord_signal() {
+        let mut recorder = Recorder::new(ActionSense::Keyboard, Some("ctrl+q".parse().unwrap()));
+        recorder.set_source(Arc::new(MockSource::scripted(vec![
+            InputEvent::KeyDown(CanonicalKey::KeyQ),
+            InputEvent::KeyUp(CanonicalKey::KeyQ),
+            InputEvent::KeyDown(CanonicalKey::RCtrl),
+            InputEvent::KeyDown(CanonicalKey::KeyQ),
+        ])));
+
+        // the key alone is recorded, the modifier of the signal is taken back
+        let script = recorder.record_sync().unwrap();
+        let actions: Vec<CanonicalAction> = script.actions.into_iter().map(|a| a.action).collect();
+        assert_eq!(actions, vec![
+            CanonicalAction::Keyboard(ActionType::Press, CanonicalKey::KeyQ),
+            CanonicalAction::Keyboard(ActionType::Release, CanonicalKey::KeyQ),
+        ]);
+    }
+
+    #[test]
+    fn record_chord_pause() {
+        let mut recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
+        recorder.set_pause_signal(Some("ctrl+p".parse().unwrap()));
+        recorder.set_source(Arc::new(MockSource::scripted(vec![
+            InputEvent::KeyDown(CanonicalKey::KeyA),
+            InputEvent::KeyUp(CanonicalKey::KeyA),
+            // pause
+            InputEvent::KeyDown(CanonicalKey::LCtrl),
+            InputEvent::KeyDown(CanonicalKey::KeyP),
+            InputEvent::KeyUp(CanonicalKey::KeyP),
+            InputEvent::KeyUp(CanonicalKey::LCtrl),
+            InputEvent::KeyDown(CanonicalKey::KeyB),
+            InputEvent::KeyUp(CanonicalKey::KeyB),
+            // resume (the modifier is released before the key this time)
+            InputEvent::KeyDown(CanonicalKey::RCtrl),
+            InputEvent::KeyDown(CanonicalKey::KeyP),
+            InputEvent::KeyUp(CanonicalKey::RCtrl),
+            InputEvent::KeyUp(CanonicalKey::KeyP),
+            InputEvent::KeyDown(CanonicalKey::KeyC),
+            InputEvent::KeyUp(CanonicalKey::KeyC),
+            InputEvent::KeyDown(CanonicalKey::Escape),
+        ])));
+
+        // neither the presses nor the releases of the signal are recorded
+        let script = recorder.record_sync().unwrap();
+        let actions: Vec<CanonicalAction> = script.actions.into_iter().map(|a| a.action).collect();
+        assert_eq!(actions, vec![
+            CanonicalAction::Keyboard(ActionType::Press, CanonicalKey::KeyA),
+            CanonicalAction::Keyboard(ActionType::Release, CanonicalKey::KeyA),
+            CanonicalAction::Marker("pause".to_string()),
+            CanonicalAction::Marker("resume".to_string()),
+            CanonicalAction::Keyboard(ActionType::Press, CanonicalKey::KeyC),
+            CanonicalAction::Keyboard(ActionType::Release, CanonicalKey::KeyC),
+        ]);
+    }
+
+    #[test]
+    fn record_auto_release() {
+        let mut recorder = Recorder::new(ActionSense::Both, Some(CanonicalKey::Escape.into()));
+        recorder.set_auto_release(true);
+        recorder.set_source(Arc::new(MockSource::scripted(vec![
+            InputEvent::KeyDown(CanonicalKey::LCtrl),
+            InputEvent::KeyDown(CanonicalKey::KeyA),
+            InputEvent::KeyUp(CanonicalKey::KeyA),
+            InputEvent::MouseDown(CanonicalButton::Left, (10, 10)),
+            InputEvent::KeyDown(CanonicalKey::Escape),
+        ])));
+
+        // what is still pressed is released (in reverse order) at the end of the script
+        let script = recorder.record_sync().unwrap();
+        let actions: Vec<CanonicalAction> = script.actions[4..].iter().map(|a| a.action.clone()).collect();
+        assert_eq!(actions, vec![
+            CanonicalAction::Mouse(ActionType::Release, CanonicalButton::Left, (10, 10)),
+            CanonicalAction::Keyboard(ActionType::Release, CanonicalKey::LCtrl),
+        ]);
+        assert!(script.actions[4..].iter().all(|a| a.timeline == script.duration));
+    }
+
+    #[test]
+    fn record_journal() {
+        let path = std::env::temp_dir().join(format!("tape-record-{}.jsonl", std::process::id()));
+        let clock = VirtualClock::new(0);
+        let source = MockSource::new();
+        let mut recorder = Recorder::new(ActionSense::Both, Some("ctrl+q".parse().unwrap()));
+        recorder.set_auto_release(true);
+        recorder.set_journal(Some(path.clone()));
+        recorder.set_source(Arc::new(source.clone()));
+        recorder.set_clock(Arc::new(clock.clone()));
+
+        let handle = recorder.record(None).unwrap();
+        while !source.is_listened() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        for ev in [
+            InputEvent::KeyDown(CanonicalKey::KeyA),
+            InputEvent::MouseMove((10, 10)),
+            InputEvent::KeyUp(CanonicalKey::KeyA),
+            InputEvent::KeyDown(CanonicalKey::LShift),
+        ] {
+            clock.advance(10);
+            source.emit(ev);
+        }
+
+        // only the trailing presses are kept in memory, the journal has the rest
+        assert_eq!(recorder.take.lock().unwrap().script.actions.len(), 1);
+        assert_eq!(journal::recover(&path).unwrap().actions.len(), 4);
+        assert_eq!(handle.snapshot().unwrap().actions.len(), 4);
+
+        // the presses of the stop signal are taken back from the journal as well
+        clock.advance(10);
+        source.emit(InputEvent::KeyDown(CanonicalKey::LCtrl));
+        source.emit(InputEvent::KeyDown(CanonicalKey::KeyQ));
+        let script = handle.join().unwrap();
+        let recovered = journal::recover(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(script.actions.len(), 5);
+        assert_eq!(script.actions[4].action, CanonicalAction::Keyboard(ActionType::Release, CanonicalKey::LShift));
+        assert_eq!(script.duration, 50);
+        assert_eq!(recovered.duration, script.duration);
+        assert_eq!(
+            recovered.actions.iter().map(|a| (a.timeline, a.action.clone())).collect::<Vec<_>>(),
+            script.actions.iter().map(|a| (a.timeline, a.action.clone())).collect::<Vec<_>>(),
+        );
+    }
+
+    #[test]
+    fn record_journal_lost() {
+        let path = std::env::temp_dir().join(format!("tape-record-lost-{}.jsonl", std::process::id()));
+        let source = MockSource::new();
+        let mut recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
+        recorder.set_journal(Some(path.clone()));
+        recorder.set_source(Arc::new(source.clone()));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        let handle = recorder.record(Some(Box::new(move |script| {
+            tx.send(script).unwrap();
+        }))).unwrap();
+        while !source.is_listened() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        source.emit(InputEvent::KeyDown(CanonicalKey::KeyA));
+        source.emit(InputEvent::KeyUp(CanonicalKey::KeyA));
+
+        // the actions dropped from memory cannot be read back once the journal is gone
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(handle.snapshot().unwrap_err(), TapeError::Journal(_)));
+
+        // the recording fails rather than losing them, and the callback is not called
+        source.emit(InputEvent::KeyDown(CanonicalKey::Escape));
+        assert!(matches!(handle.join().unwrap_err(), TapeError::Journal(_)));
+        assert!(rx.try_recv().is_err());
+        assert!(!recorder.is_working());
+    }
+
+    #[test]
+    fn record_already_working() {
+        let source = MockSource::new();
+        let mut recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
+        recorder.set_source(Arc::new(source.clone()));
+
+        recorder.record(None).unwrap();
+        assert_eq!(recorder.record(None).unwrap_err(), TapeError::AlreadyWorking);
+        assert_eq!(recorder.record_sync().unwrap_err(), TapeError::AlreadyWorking);
+
+        recorder.finish();
+    }
+
+    #[test]
+    fn record_handle() {
+        let clock = VirtualClock::new(0);
+        let source = MockSource::new();
+        let mut recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
+        recorder.set_source(Arc::new(source.clone()));
+        recorder.set_clock(Arc::new(clock.clone()));
+
+        let handle = recorder.record(None).unwrap();
+        while !source.is_listened() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        clock.advance(100);
+        source.emit(InputEvent::KeyDown(CanonicalKey::KeyA));
+        clock.advance(100);
+
+        // the snapshot does not end the recording
+        let snapshot = handle.snapshot().unwrap();
+        assert_eq!(snapshot.actions.len(), 1);
+        assert_eq!(snapshot.duration, 200);
+        assert!(handle.is_working());
+        assert!(recorder.is_working());
+
+        clock.advance(100);
+        source.emit(InputEvent::KeyUp(CanonicalKey::KeyA));
+        handle.stop();
+        let script = handle.join().unwrap();
+        assert_eq!(script.actions.len(), 2);
+        assert_eq!(script.duration, 300);
+        assert!(!recorder.is_working());
+
+        // the stop signal ends the recording as well
+        let handle = recorder.record(None).unwrap();
+        while !source.is_listened() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        source.emit(InputEvent::KeyDown(CanonicalKey::Escape));
+        assert!(handle.join().unwrap().actions.is_empty());
+    }
+
+    #[test]
+    fn record_pause() {
+        let clock = VirtualClock::new(0);
+        let source = MockSource::new();
+        let mut recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
+        recorder.set_pause_signal(Some(CanonicalKey::Pause.into()));
+        recorder.set_source(Arc::new(source.clone()));
+        recorder.set_clock(Arc::new(clock.clone()));
+
+        let handle = recorder.record(None).unwrap();
+        while !source.is_listened() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        clock.advance(100);
+        source.emit(InputEvent::KeyDown(CanonicalKey::KeyA));
+
+        // the events are dropped during the pause
+        clock.advance(100);
+        recorder.pause();
+        assert!(handle.is_paused());
+        clock.advance(100);
+        source.emit(InputEvent::KeyDown(CanonicalKey::KeyB));
+        clock.advance(900);
+        assert_eq!(handle.snapshot().unwrap().duration, 200);
+        recorder.resume();
+
+        // the time paused is left out of the later timeline
+        clock.advance(100);
+        source.emit(InputEvent::KeyUp(CanonicalKey::KeyA));
+
+        // so does the pause by the pause signal (which is not recorded)
+        source.emit(InputEvent::KeyDown(CanonicalKey::Pause));
+        source.emit(InputEvent::KeyUp(CanonicalKey::Pause));
+        assert!(recorder.is_paused());
+        clock.advance(500);
+        source.emit(InputEvent::KeyDown(CanonicalKey::Pause));
+        clock.advance(100);
+        handle.stop();
+
+        let script = handle.join().unwrap();
+        let actions: Vec<(i64, CanonicalAction)> = script.actions.into_iter().map(|a| (a.timeline, a.action)).collect();
+        assert_eq!(actions, vec![
+            (100, CanonicalAction::Keyboard(ActionType::Press, CanonicalKey::KeyA)),
+            (200, CanonicalAction::Marker("pause".to_string())),
+            (200, CanonicalAction::Marker("resume".to_string())),
+            (300, CanonicalAction::Keyboard(ActionType::Release, CanonicalKey::KeyA)),
+            (300, CanonicalAction::Marker("pause".to_string())),
+            (300, CanonicalAction::Marker("resume".to_string())),
+        ]);
+        assert_eq!(script.duration, 400);
+    }
+
+    #[test]
+    fn record_finish_latency() {
+        let source = MockSource::new();
+        let mut recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
+        recorder.set_source(Arc::new(source.clone()));
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        recorder.record(Some(Box::new(move |script| {
+            tx.send(script).unwrap();
+        }))).unwrap();
+        while !source.is_listened() {
+            thread::sleep(Duration::from_millis(1));
+        }
+        source.emit(InputEvent::KeyDown(CanonicalKey::KeyA));
+
+        // the recording stops once it is asked to (the bound is just a guard against hanging)
+        source.emit(InputEvent::KeyDown(CanonicalKey::Escape));
+        let script = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        assert_eq!(script.actions.len(), 1);
+        assert!(!recorder.is_working());
+        assert!(!source.is_listened());
+    }
 
     #[test]
     fn record() {
-        let recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape));
+        let recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
         recorder.record(Some(Box::new(|script| {
             println!("script: {:?}", script.duration);
-        })));
+        }))).unwrap();
 
         thread::sleep(Duration::from_secs(5));
 
@@ -382,12 +1007,12 @@ mod unit_test {
 
     #[test]
     fn record_sync() {
-        let recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape));
+        let recorder = Recorder::new(ActionSense::Keyboard, Some(CanonicalKey::Escape.into()));
 
         // here, we use Box to create a pointer to the closure,
         // rather then capture the variable 'recorder'.
         // so that we can reuse the recorder later.
-        let task: Box<dyn Fn() -> Result<Script, ()>> = Box::new(|| recorder.record_sync());
+        let task: Box<dyn Fn() -> Result<Script, TapeError>> = Box::new(|| recorder.record_sync());
 
         // first use of recorder
         match task() {
@@ -0,0 +1,28 @@
+import test from 'ava'
+import {Recorder} from '../index.js'
+
+// poll until the condition holds (for 5 seconds at most)
+const until = async (condition) => {
+    for (let i = 0; i < 500 && !condition(); i++) {
+        await new Promise(resolve => setTimeout(resolve, 10))
+    }
+    return condition()
+}
+
+test('pause and resume while recording asynchronously', async (t) => {
+    const recorder = new Recorder('keyboard', 'ctrl+shift+f12')
+    const recording = recorder.recordAsync()
+    t.true(await until(() => recorder.isWorking()))
+
+    // the recorder is not held by the recording, so it can be controlled in the meantime
+    recorder.pause()
+    t.true(recorder.isPaused())
+    recorder.resume()
+    t.false(recorder.isPaused())
+    recorder.finish()
+
+    const script = await recording
+    const markers = script.actions.filter(v => v.actionType === 'Marker').map(v => v.actionKey)
+    t.deepEqual(markers, ['pause', 'resume'])
+    t.false(recorder.isWorking())
+})
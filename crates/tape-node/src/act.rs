@@ -4,11 +4,24 @@ use napi::{
     Env, Error, JsFunction, JsUndefined, Status, Task,
     threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode},
 };
-use tape_core::act::Actor;
-use crate::ffi_adapter::FfiSafeScript;
+use tape_core::act::{Actor, Offset, Repeat};
+use tape_core::error::TapeError;
+use tape_core::canonicalize::Script;
+use crate::ffi_adapter::{FfiSafeProgress, FfiSafeScript, to_js_error, to_signal};
+
+/// Convert the offset from js, "time" for the time (in milliseconds) on the timeline and "action" for the index of the action
+fn to_offset(offset_type: &str, value: i64) -> Result<Offset> {
+    match offset_type {
+        "time" => Ok(Offset::Time(value)),
+        "action" => Ok(Offset::Action(value.max(0) as usize)),
+        _ => Err(Error::new(Status::InvalidArg, format!("invalid offset type '{}'", offset_type))),
+    }
+}
 
 pub struct AsyncAct {
     worker: Arc<Mutex<Actor>>,
+    /// The error of the task (if any), to be rejected with
+    failure: Option<TapeError>,
 }
 
 impl Task for AsyncAct {
@@ -16,16 +29,38 @@ impl Task for AsyncAct {
     type JsValue = ();
 
     fn compute(&mut self) -> napi::Result<Self::Output> {
-        self.worker.lock().unwrap().act_sync()
-            .map_err(|_| Error::new(Status::WouldDeadlock, "no stop signal set before calling act_async"))
+        // the actor is locked only to start the acting, so that it can still be controlled (e.g. paused) while acting
+        let started = {
+            let mut actor = self.worker.lock().unwrap();
+            match actor.is_endless() {
+                true => Err(TapeError::NoStopSignal),
+                false => actor.act(None),
+            }
+        };
+
+        match started {
+            Ok(handle) => {
+                handle.join();
+                Ok(())
+            }
+            Err(e) => {
+                let err = Error::new(Status::GenericFailure, e.to_string());
+                self.failure = Some(e);
+                Err(err)
+            }
+        }
     }
 
     fn resolve(&mut self, _env: Env, _output: Self::Output) -> napi::Result<Self::JsValue> {
         Ok(())
     }
 
-    fn reject(&mut self, _env: Env, err: Error) -> napi::Result<Self::JsValue> {
-        Err(err)
+    fn reject(&mut self, env: Env, err: Error) -> napi::Result<Self::JsValue> {
+        // reject with the code of the error if it comes from tape
+        match self.failure.take() {
+            Some(e) => Err(to_js_error(env, &e)),
+            None => Err(err),
+        }
     }
 }
 
@@ -40,27 +75,35 @@ impl NodeActor {
     /// - script: the script to act, can be changed later
     /// - cyclic: whether the actor is acting cyclically
     /// - act_type: the type of the acter, can be "keyboard", "mouse" or "both"
-    /// - stop_signal: the signal to stop the acter, can be one of valid 'CanonicalKey'
+    /// - stop_signal: the signal to stop the acter, can be one of valid 'CanonicalKey' or a chord of them like "ctrl+shift+q"
+    ///
+    /// Throw an error with code `TAPE_INVALID_CHORD` if the stop signal (or a chord in the script) is not a valid chord.
     #[napi(constructor)]
     pub fn new(
+        env: Env,
         script: FfiSafeScript,
         cyclic: bool,
         #[napi(ts_arg_type = "'keyboard' | 'mouse' | 'both'")]
         act_type: String,
         stop_signal: Option<String>,
-    ) -> NodeActor {
-        let worker = Actor::new(script.into(), cyclic, act_type.into(), stop_signal.map(Into::into));
+    ) -> Result<NodeActor> {
+        let script = Script::try_from(script).map_err(|e| to_js_error(env, &e))?;
+        let worker = Actor::new(script, cyclic, act_type.into(), to_signal(env, stop_signal)?);
 
-        NodeActor { inner: Arc::new(Mutex::new(worker)) }
+        Ok(NodeActor { inner: Arc::new(Mutex::new(worker)) })
     }
 
 
     /// Set the script to be acted.
     ///
     /// This has no effect on the current acting. (The script is cloned once `act_callback` or `act_async` is called)
+    ///
+    /// Throw an error with code `TAPE_INVALID_CHORD` if a chord in the script is not a valid chord.
     #[napi]
-    pub fn new_script(&self, script: FfiSafeScript) {
-        self.inner.lock().unwrap().new_script(script.into());
+    pub fn new_script(&self, env: Env, script: FfiSafeScript) -> Result<()> {
+        let script = Script::try_from(script).map_err(|e| to_js_error(env, &e))?;
+        self.inner.lock().unwrap().new_script(script);
+        Ok(())
     }
 
     /// Set whether the actor is acting cyclically.
@@ -71,6 +114,52 @@ impl NodeActor {
         self.inner.lock().unwrap().set_cyclic(cyclic);
     }
 
+    /// Set how many times the actor performs the script.
+    ///
+    /// - policy: "once", "times" (`value` times), "forever" or "until" (the time `value` in milliseconds since the unix epoch,
+    /// the loop going on at that time is performed to the end)
+    ///
+    /// Can affect the current acting cause the policy will be checked before every loop.
+    #[napi]
+    pub fn set_repeat(
+        &self,
+        #[napi(ts_arg_type = "'once' | 'times' | 'forever' | 'until'")]
+        policy: String,
+        value: Option<i64>,
+    ) -> Result<()> {
+        let repeat = match (policy.as_str(), value) {
+            ("once", _) => Repeat::Once,
+            ("times", Some(times)) => Repeat::Times(times.max(0) as usize),
+            ("forever", _) => Repeat::Forever,
+            ("until", Some(deadline)) => Repeat::Until(deadline),
+            _ => return Err(Error::new(Status::InvalidArg, format!("invalid repeat policy '{}' with value {:?}", policy, value))),
+        };
+        self.inner.lock().unwrap().set_repeat(repeat);
+        Ok(())
+    }
+
+    /// Set the delay between two loops in milliseconds (default to 0).
+    ///
+    /// Can affect the current acting cause the delay will be checked before every loop.
+    #[napi]
+    pub fn set_loop_delay(&self, delay: i64) {
+        self.inner.lock().unwrap().set_loop_delay(delay);
+    }
+
+    /// Set where the first loop starts (default to the beginning), the following loops start from the beginning.
+    ///
+    /// This has no effect on the current acting. (The offset is copied once `act_callback` or `act_async` is called)
+    #[napi]
+    pub fn set_start(
+        &self,
+        #[napi(ts_arg_type = "'time' | 'action'")]
+        offset_type: String,
+        value: i64,
+    ) -> Result<()> {
+        self.inner.lock().unwrap().set_start(to_offset(&offset_type, value)?);
+        Ok(())
+    }
+
     /// Set the type of the action to be acted.
     ///
     /// This has no effect on the current acting. (The script is cloned and filtered once `act_callback` or `act_async` is called)
@@ -86,9 +175,30 @@ impl NodeActor {
     /// Set the key that stops the acting
     ///
     /// This has no effect on the current acting. (The signal is copied once `act_callback` or `act_async` is called.)
+    /// Throw an error with code `TAPE_INVALID_CHORD` if the signal is not a valid chord.
+    #[napi]
+    pub fn set_stop_signal(&self, env: Env, stop_signal: Option<String>) -> Result<()> {
+        self.inner.lock().unwrap().set_stop_signal(to_signal(env, stop_signal)?);
+        Ok(())
+    }
+
+    /// Set the speed of acting (default to 1), e.g. 2 for twice as fast and 0.5 for half as fast.
+    ///
+    /// Can affect the acting started by `act_callback`, which goes on from where it is at the new speed.
+    /// Throw an error with code `TAPE_INVALID_SPEED` if the speed is not a positive number.
+    #[napi]
+    pub fn set_speed(&self, env: Env, speed: f64) -> Result<()> {
+        self.inner.lock().unwrap().set_speed(speed).map_err(|e| to_js_error(env, &e))
+    }
+
+    /// Set the key that pauses the acting, and resumes it once pressed again
+    ///
+    /// This has no effect on the current acting. (The signal is copied once `act_callback` or `act_async` is called.)
+    /// Throw an error with code `TAPE_INVALID_CHORD` if the signal is not a valid chord.
     #[napi]
-    pub fn set_stop_signal(&self, stop_signal: Option<String>) {
-        self.inner.lock().unwrap().set_stop_signal(stop_signal.map(Into::into));
+    pub fn set_pause_signal(&self, env: Env, pause_signal: Option<String>) -> Result<()> {
+        self.inner.lock().unwrap().set_pause_signal(to_signal(env, pause_signal)?);
+        Ok(())
     }
 
     /// Start acting (The act will stop when the stop signal is received,
@@ -107,6 +217,7 @@ impl NodeActor {
     #[napi]
     pub fn act_callback(
         &self,
+        env: Env,
         #[napi(ts_arg_type = "() => void")]
         on_finish: JsFunction,
     ) -> Result<()> {
@@ -118,9 +229,19 @@ impl NodeActor {
 
         self.inner.lock().unwrap().act(Some(Box::new(move || {
             tsfn.call((), ThreadsafeFunctionCallMode::NonBlocking);
-        })));
+        })))
+            // the acting goes on in the background, its progress is told by `progress`
+            .map(|_handle| ())
+            .map_err(|e| to_js_error(env, &e))
+    }
 
-        Ok(())
+    /// Get the progress of the current (or the last) acting, started by `act_callback` or `act_async`
+    /// (all zero if it has never been started)
+    ///
+    /// Once the acting is finished, the progress stays where it stops.
+    #[napi]
+    pub fn progress(&self) -> FfiSafeProgress {
+        self.inner.lock().unwrap().progress().into()
     }
 
     /// Interrupt the acting started by `act_callback`
@@ -131,9 +252,43 @@ impl NodeActor {
         Ok(())
     }
 
+    /// Pause the acting started by `act_callback`.
+    ///
+    /// The remaining delay to the next action is preserved, and the held keys/buttons are released until `resume` is called.
+    #[napi]
+    pub fn pause(&self) -> Result<()> {
+        self.inner.lock().unwrap().pause();
+
+        Ok(())
+    }
+
+    /// Resume the acting paused by `pause` (or the pause signal)
+    #[napi]
+    pub fn resume(&self) -> Result<()> {
+        self.inner.lock().unwrap().resume();
+
+        Ok(())
+    }
+
+    /// Move the paused acting to the offset in the current loop, it goes on from there once resumed
+    /// (throw if the acting is not paused)
+    #[napi]
+    pub fn seek(
+        &self,
+        env: Env,
+        #[napi(ts_arg_type = "'time' | 'action'")]
+        offset_type: String,
+        value: i64,
+    ) -> Result<()> {
+        let offset = to_offset(&offset_type, value)?;
+        self.inner.lock().unwrap().seek(offset).map_err(|e| to_js_error(env, &e))
+    }
+
     /// Start acting (The act will not stop until the stop signal is received,
     /// that is, you have to set the stop signal before calling this function or it will throw an error directly).
     ///
+    /// If it fails, the `Promise` will be rejected with an error whose `code` tells why (e.g. `TAPE_NO_STOP_SIGNAL`, `TAPE_ALREADY_WORKING`).
+    ///
     /// This will run in a separate thread (created by `libuv`), so it will not block the main thread.
     ///
     /// The `Promise` will be resolved after the entire task ends.
@@ -144,6 +299,6 @@ impl NodeActor {
     #[napi(ts_return_type = "Promise<void>")]
     pub fn act_async(&self) -> AsyncTask<AsyncAct> {
         let shared_ptr = self.inner.clone();
-        AsyncTask::new(AsyncAct { worker: shared_ptr })
+        AsyncTask::new(AsyncAct { worker: shared_ptr, failure: None })
     }
 }
\ No newline at end of file
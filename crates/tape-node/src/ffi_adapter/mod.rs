@@ -1,5 +1,42 @@
+use napi::{Env, Error, JsError};
+use tape_core::act::Progress;
 use tape_core::canonicalize::{Action, Script};
-use tape_core::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalKey};
+use tape_core::canonicalize::declaration::{ActionType, CanonicalAction, CanonicalChord, CanonicalKey};
+use tape_core::error::TapeError;
+
+/// The `code` of the JS error for each kind of [TapeError]
+pub fn error_code(err: &TapeError) -> &'static str {
+    match err {
+        TapeError::Parse { .. } => "TAPE_PARSE",
+        TapeError::SelfCheck { .. } => "TAPE_SELF_CHECK",
+        TapeError::Lint(_) => "TAPE_LINT",
+        TapeError::UnsupportedVersion { .. } => "TAPE_UNSUPPORTED_VERSION",
+        TapeError::InvalidRange { .. } => "TAPE_INVALID_RANGE",
+        TapeError::Serialize(_) => "TAPE_SERIALIZE",
+        TapeError::Journal(_) => "TAPE_JOURNAL",
+        TapeError::NoStopSignal => "TAPE_NO_STOP_SIGNAL",
+        TapeError::AlreadyWorking => "TAPE_ALREADY_WORKING",
+        TapeError::NotPaused => "TAPE_NOT_PAUSED",
+        TapeError::InvalidSpeed(_) => "TAPE_INVALID_SPEED",
+        TapeError::InvalidChord(_) => "TAPE_INVALID_CHORD",
+        TapeError::UnknownFormat(_) => "TAPE_UNKNOWN_FORMAT",
+        TapeError::UnmappableKey(_) => "TAPE_UNMAPPABLE_KEY",
+        TapeError::UnmappableButton(_) => "TAPE_UNMAPPABLE_BUTTON",
+    }
+}
+
+/// Convert a [TapeError] into a JS error whose `code` tells the kind of the error (see [error_code])
+pub fn to_js_error(env: Env, err: &TapeError) -> Error {
+    let js_error = JsError::from(Error::new(error_code(err), err.to_string()));
+    Error::from(js_error.into_unknown(env))
+}
+
+/// Parse the signal from js (if any), throw an error with code `TAPE_INVALID_CHORD` if it is not a valid chord
+pub fn to_signal(env: Env, signal: Option<String>) -> napi::Result<Option<CanonicalChord>> {
+    signal.map(|v| v.parse::<CanonicalChord>())
+        .transpose()
+        .map_err(|e| to_js_error(env, &e))
+}
 
 #[napi(object)]
 pub struct FFISafeAction {
@@ -8,17 +45,29 @@ pub struct FFISafeAction {
     /// The time since the beginning of the script
     pub timeline: i64,
     /// The type of the action
-    #[napi(ts_type = "'KeyPress' | 'KeyRelease' | 'MousePress' | 'MouseRelease' | 'MouseMove'")]
+    #[napi(ts_type = "'KeyPress' | 'KeyRelease' | 'KeyChord' | 'MousePress' | 'MouseRelease' | 'MouseMove' | 'MouseScroll' | 'Text' | 'Marker' | 'Comment' | 'Wait'")]
     pub action_type: String,
-    /// The key of the action
+    /// The key of the action (the chord like "ctrl+shift+t" for a chord, the label for a marker, the text for a text or a comment)
     pub action_key: String,
-    /// The position of the action (only for mouse action)
+    /// The position of the action (only for mouse action), or the steps scrolled horizontally and vertically (for 'MouseScroll')
     #[napi(ts_type = "[x: number, y: number] | null")]
     pub action_position: Option<Vec<i32>>,
+    /// The delay between two characters in milliseconds for 'Text' (typed at once if not set), or the milliseconds to wait for 'Wait'
+    pub action_delay: Option<i64>,
 }
 
 impl From<Action> for FFISafeAction {
     fn from(value: Action) -> Self {
+        let action_position = match value.action {
+            CanonicalAction::Scroll((dx, dy)) => Some(vec![dx, dy]),
+            _ => None,
+        };
+        let action_delay = match value.action {
+            CanonicalAction::Text { delay, .. } => delay,
+            CanonicalAction::Wait(ms) => Some(ms),
+            _ => None,
+        };
+
         FFISafeAction {
             ctime: value.ctime,
             timeline: value.timeline,
@@ -34,34 +83,56 @@ impl From<Action> for FFISafeAction {
                     ActionType::Release => "MouseRelease",
                     ActionType::Move => "MouseMove",
                 },
+                CanonicalAction::Chord(_) => "KeyChord",
+                CanonicalAction::Scroll(_) => "MouseScroll",
+                CanonicalAction::Text { .. } => "Text",
+                CanonicalAction::Marker(_) => "Marker",
+                CanonicalAction::Comment(_) => "Comment",
+                CanonicalAction::Wait(_) => "Wait",
             }.to_string(),
             action_key: match value.action {
                 CanonicalAction::Keyboard(_, kkey) => kkey.to_string(),
                 CanonicalAction::Mouse(_, mkey, _) => mkey.to_string(),
+                CanonicalAction::Chord(chord) => chord.to_string(),
+                CanonicalAction::Scroll(_) => String::new(),
+                CanonicalAction::Text { text, .. } => text,
+                CanonicalAction::Marker(label) => label,
+                CanonicalAction::Comment(text) => text,
+                CanonicalAction::Wait(_) => String::new(),
             },
-            action_position: None,
+            action_position,
+            action_delay,
         }
     }
 }
 
-impl Into<Action> for FFISafeAction {
-    fn into(self) -> Action {
-        let pos = self.action_position
+/// Convert the action from js, fail with [TapeError::InvalidChord] if the chord of a "KeyChord" is not valid
+impl TryFrom<FFISafeAction> for Action {
+    type Error = TapeError;
+
+    fn try_from(value: FFISafeAction) -> Result<Self, Self::Error> {
+        let pos = value.action_position
             .map_or((0, 0), |v| if v.len() < 2 { (0, 0) } else { (v[0], v[1]) });
 
-        Action {
-            ctime: self.ctime,
-            timeline: self.timeline,
-            action: match self.action_type.as_str() {
-                "KeyPress" => CanonicalAction::Keyboard(ActionType::Press, self.action_key.into()),
-                "KeyRelease" => CanonicalAction::Keyboard(ActionType::Release, self.action_key.into()),
-                "MousePress" => CanonicalAction::Mouse(ActionType::Press, self.action_key.into(), pos),
-                "MouseRelease" => CanonicalAction::Mouse(ActionType::Release, self.action_key.into(), pos),
-                "MouseMove" => CanonicalAction::Mouse(ActionType::Move, self.action_key.into(), pos),
+        Ok(Action {
+            ctime: value.ctime,
+            timeline: value.timeline,
+            action: match value.action_type.as_str() {
+                "KeyPress" => CanonicalAction::Keyboard(ActionType::Press, value.action_key.into()),
+                "KeyRelease" => CanonicalAction::Keyboard(ActionType::Release, value.action_key.into()),
+                "MousePress" => CanonicalAction::Mouse(ActionType::Press, value.action_key.into(), pos),
+                "MouseRelease" => CanonicalAction::Mouse(ActionType::Release, value.action_key.into(), pos),
+                "MouseMove" => CanonicalAction::Mouse(ActionType::Move, value.action_key.into(), pos),
+                "KeyChord" => CanonicalAction::Chord(value.action_key.parse()?),
+                "MouseScroll" => CanonicalAction::Scroll(pos),
+                "Text" => CanonicalAction::Text { text: value.action_key, delay: value.action_delay },
+                "Marker" => CanonicalAction::Marker(value.action_key),
+                "Comment" => CanonicalAction::Comment(value.action_key),
+                "Wait" => CanonicalAction::Wait(value.action_delay.unwrap_or(0)),
                 // this should never happen
                 _ => CanonicalAction::Keyboard(ActionType::Press, CanonicalKey::Unknown),
             },
-        }
+        })
     }
 }
 
@@ -88,14 +159,39 @@ impl From<Script> for FfiSafeScript {
     }
 }
 
-impl From<FfiSafeScript> for Script {
-    fn from(value: FfiSafeScript) -> Self {
-        Script {
-            cursor: 0,
-            name: value.name,
-            ctime: value.ctime,
+/// Convert the script from js, fail if any of the actions cannot be converted (see [Action::try_from])
+impl TryFrom<FfiSafeScript> for Script {
+    type Error = TapeError;
+
+    fn try_from(value: FfiSafeScript) -> Result<Self, Self::Error> {
+        let mut script = Script::empty();
+        script.name = value.name;
+        script.ctime = value.ctime;
+        script.duration = value.duration;
+        script.actions = value.actions.into_iter().map(Action::try_from).collect::<Result<_, _>>()?;
+        Ok(script)
+    }
+}
+
+#[napi(object)]
+pub struct FfiSafeProgress {
+    /// The number of actions performed in the current loop
+    pub cursor: u32,
+    /// The time elapsed on the timeline of the current loop in milliseconds
+    pub elapsed: i64,
+    /// The index of the current loop (0 for the first loop)
+    pub iteration: u32,
+    /// The duration of the script in milliseconds
+    pub duration: i64,
+}
+
+impl From<Progress> for FfiSafeProgress {
+    fn from(value: Progress) -> Self {
+        FfiSafeProgress {
+            cursor: value.cursor as u32,
+            elapsed: value.elapsed,
+            iteration: value.iteration as u32,
             duration: value.duration,
-            actions: value.actions.into_iter().map(|action| action.into()).collect(),
         }
     }
 }
@@ -1,4 +1,5 @@
 use std::sync::{Arc, Mutex};
+use std::path::PathBuf;
 use napi::{
     bindgen_prelude::{AsyncTask, Result},
     Env, Error, JsFunction, Status, Task,
@@ -6,10 +7,13 @@ use napi::{
 };
 use tape_core::canonicalize::Script;
 use tape_core::record::Recorder;
-use crate::ffi_adapter::{FfiSafeScript};
+use tape_core::error::TapeError;
+use crate::ffi_adapter::{FfiSafeScript, to_js_error, to_signal};
 
 pub struct AsyncRecord {
     worker: Arc<Mutex<Recorder>>,
+    /// The error of the task (if any), to be rejected with
+    failure: Option<TapeError>,
 }
 
 impl Task for AsyncRecord {
@@ -17,16 +21,35 @@ impl Task for AsyncRecord {
     type JsValue = FfiSafeScript;
 
     fn compute(&mut self) -> Result<Self::Output> {
-        self.worker.lock().unwrap().record_sync()
-            .map_err(|_| Error::new(Status::WouldDeadlock, "no stop signal set before calling record_async"))
+        // the recorder is locked only to start the recording, so that it can still be controlled (e.g. paused) while recording
+        let started = {
+            let recorder = self.worker.lock().unwrap();
+            match recorder.has_stop_signal() {
+                true => recorder.record(None),
+                false => Err(TapeError::NoStopSignal),
+            }
+        };
+
+        match started.and_then(|handle| handle.join()) {
+            Ok(script) => Ok(script),
+            Err(e) => {
+                let err = Error::new(Status::GenericFailure, e.to_string());
+                self.failure = Some(e);
+                Err(err)
+            }
+        }
     }
 
     fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
         Ok(output.into())
     }
 
-    fn reject(&mut self, _env: Env, err: Error) -> Result<Self::JsValue> {
-        Err(err)
+    fn reject(&mut self, env: Env, err: Error) -> Result<Self::JsValue> {
+        // reject with the code of the error if it comes from tape
+        match self.failure.take() {
+            Some(e) => Err(to_js_error(env, &e)),
+            None => Err(err),
+        }
     }
 }
 
@@ -39,16 +62,19 @@ pub struct NodeRecorder {
 impl NodeRecorder {
     /// create a new recorder
     /// - record_type: the type of the recorder, can be "keyboard", "mouse" or "both"
-    /// - stop_signal: the signal to stop the recorder, can be one of valid 'CanonicalKey'
+    /// - stop_signal: the signal to stop the recorder, can be one of valid 'CanonicalKey' or a chord of them like "ctrl+shift+q"
+    ///
+    /// Throw an error with code `TAPE_INVALID_CHORD` if the stop signal is not a valid chord.
     #[napi(constructor)]
     pub fn new(
+        env: Env,
         #[napi(ts_arg_type = "'keyboard' | 'mouse' | 'both'")]
         record_type: String,
         stop_signal: Option<String>,
-    ) -> NodeRecorder {
-        let worker = Recorder::new(record_type.into(), stop_signal.map(Into::into));
+    ) -> Result<NodeRecorder> {
+        let worker = Recorder::new(record_type.into(), to_signal(env, stop_signal)?);
 
-        NodeRecorder { inner: Arc::new(Mutex::new(worker)) }
+        Ok(NodeRecorder { inner: Arc::new(Mutex::new(worker)) })
     }
 
     /// Set the type of the action to be recorded
@@ -66,9 +92,38 @@ impl NodeRecorder {
     /// Set the key that stops the recording
     ///
     /// This has no effect on the current recording. (The signal is copied once `record_callback` or `record_async` is called.)
+    /// Throw an error with code `TAPE_INVALID_CHORD` if the signal is not a valid chord.
     #[napi]
-    pub fn set_stop_signal(&self, stop_signal: Option<String>) {
-        self.inner.lock().unwrap().set_stop_signal(stop_signal.map(Into::into));
+    pub fn set_stop_signal(&self, env: Env, stop_signal: Option<String>) -> Result<()> {
+        self.inner.lock().unwrap().set_stop_signal(to_signal(env, stop_signal)?);
+        Ok(())
+    }
+
+    /// Set the key that pauses the recording, and resumes it once pressed again
+    ///
+    /// This has no effect on the current recording. (The signal is copied once `record_callback` or `record_async` is called.)
+    /// Throw an error with code `TAPE_INVALID_CHORD` if the signal is not a valid chord.
+    #[napi]
+    pub fn set_pause_signal(&self, env: Env, pause_signal: Option<String>) -> Result<()> {
+        self.inner.lock().unwrap().set_pause_signal(to_signal(env, pause_signal)?);
+        Ok(())
+    }
+
+    /// Set whether to add the releases of the keys and buttons still pressed once the recording stops (default to false)
+    ///
+    /// This has no effect on the current recording. (The option is copied once `record_callback` or `record_async` is called.)
+    #[napi]
+    pub fn set_auto_release(&self, auto_release: bool) {
+        self.inner.lock().unwrap().set_auto_release(auto_release);
+    }
+
+    /// Set the file to write the script to as it is being recorded (default to null, i.e. no journal),
+    /// so that the script survives if the process dies during the recording
+    ///
+    /// This has no effect on the current recording. (The path is copied once `record_callback` or `record_async` is called.)
+    #[napi]
+    pub fn set_journal(&self, path: Option<String>) {
+        self.inner.lock().unwrap().set_journal(path.map(PathBuf::from));
     }
 
     /// Start recording (The record will stop when the stop signal is received,
@@ -82,6 +137,7 @@ impl NodeRecorder {
     #[napi]
     pub fn record_callback(
         &self,
+        env: Env,
         #[napi(ts_arg_type = "(v: FfiSafeScript) => void")]
         on_finish: JsFunction,
     ) -> Result<()> {
@@ -92,12 +148,25 @@ impl NodeRecorder {
 
         self.inner.lock().unwrap().record(Some(Box::new(move |script| {
             tsfn.call(script.into(), ThreadsafeFunctionCallMode::NonBlocking);
-        })));
+        })))
+            // the recording goes on in the background, it is stopped by the stop signal or `finish`
+            .map(|_handle| ())
+            .map_err(|e| to_js_error(env, &e))
+    }
 
-        Ok(())
+    /// Whether the recorder is working (including the paused one)
+    #[napi]
+    pub fn is_working(&self) -> bool {
+        self.inner.lock().unwrap().is_working()
+    }
+
+    /// Whether the recording is paused
+    #[napi]
+    pub fn is_paused(&self) -> bool {
+        self.inner.lock().unwrap().is_paused()
     }
 
-    /// Interrupt the recording started by `record_callback`
+    /// Interrupt the recording started by `record_callback` or `record_async`
     #[napi]
     pub fn finish(&self) -> Result<()> {
         self.inner.lock().unwrap().finish();
@@ -105,9 +174,29 @@ impl NodeRecorder {
         Ok(())
     }
 
+    /// Pause the recording started by `record_callback` or `record_async`.
+    ///
+    /// The events are dropped until `resume` is called, and the time paused is left out of the script.
+    #[napi]
+    pub fn pause(&self) -> Result<()> {
+        self.inner.lock().unwrap().pause();
+
+        Ok(())
+    }
+
+    /// Resume the recording paused by `pause` (or the pause signal)
+    #[napi]
+    pub fn resume(&self) -> Result<()> {
+        self.inner.lock().unwrap().resume();
+
+        Ok(())
+    }
+
     /// Start recording (The record will not stop until the stop signal is received,
     /// that is, you have to set the stop signal before calling this function or it will throw an error directly).
     ///
+    /// If it fails, the `Promise` will be rejected with an error whose `code` tells why (e.g. `TAPE_NO_STOP_SIGNAL`, `TAPE_ALREADY_WORKING`).
+    ///
     /// This will run in a separate thread (created by `libuv`), so it will not block the main thread.
     ///
     /// ---
@@ -115,6 +204,6 @@ impl NodeRecorder {
     #[napi(ts_return_type = "Promise<FfiSafeScript>")]
     pub fn record_async(&self) -> AsyncTask<AsyncRecord> {
         let shared_ptr = self.inner.clone();
-        AsyncTask::new(AsyncRecord { worker: shared_ptr })
+        AsyncTask::new(AsyncRecord { worker: shared_ptr, failure: None })
     }
 }
\ No newline at end of file